@@ -1,8 +1,10 @@
-use std::cell::Cell;
-use std::collections::VecDeque;
+use std::cell::{Cell, RefCell};
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::rc::Rc;
+use std::sync::atomic::{self, AtomicU64};
 
-use godot::engine::{Control, Node, Node2D};
+use godot::engine::{CanvasItem, Control, Node, Node2D};
 use godot::obj::WithBaseField;
 use godot::prelude::*;
 
@@ -14,63 +16,324 @@ unsafe impl ExtensionLibrary for MyExtension {}
 struct WorldView<'a> {
     time: &'a GameTime,
     apple_stock: i64,
+    day_kind: DayKind,
+    eat_reserve: Option<f64>,
+    work_aversion: f64,
+    theft_window: bool,
+    goal_met: Option<bool>,
 }
 
+#[derive(Clone, Copy, PartialEq)]
+enum DayKind {
+    Ordinary,
+    Gather,
+    Feast,
+}
+
+#[derive(Clone, Copy, PartialEq)]
 enum Personality {
     Cooperative,
     Greedy,
+    Lazy,
 }
 
-struct Character {
-    graphics: Gd<Node2D>,
-    task: Cell<Task>,
-    personality: Personality,
+impl Personality {
+    fn parse(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "cooperative" => Some(Personality::Cooperative),
+            "greedy" => Some(Personality::Greedy),
+            "lazy" => Some(Personality::Lazy),
+            _ => None,
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Personality::Cooperative => "Cooperative",
+            Personality::Greedy => "Greedy",
+            Personality::Lazy => "Lazy",
+        }
+    }
 }
 
-impl Character {
-    fn new(node: Gd<Node2D>) -> Self {
-        let personality = if node.get_name().hash() % 4 == 1 {
+#[derive(Clone, Copy, PartialEq)]
+#[cfg_attr(test, derive(Default))]
+struct PersonalityWeights {
+    cooperative: u32,
+    greedy: u32,
+    lazy: u32,
+}
+
+impl PersonalityWeights {
+    const LEGACY: PersonalityWeights = PersonalityWeights {
+        cooperative: 3,
+        greedy: 1,
+        lazy: 0,
+    };
+
+    fn assign(&self, hash: u32) -> Personality {
+        let total = u64::from(self.cooperative) + u64::from(self.greedy) + u64::from(self.lazy);
+        let bucket = (u64::from(hash) + total - 1) % total;
+        if bucket < u64::from(self.greedy) {
             Personality::Greedy
+        } else if bucket < u64::from(self.greedy) + u64::from(self.lazy) {
+            Personality::Lazy
         } else {
             Personality::Cooperative
-        };
+        }
+    }
+}
+
+struct Character<G = Gd<Node2D>> {
+    graphics: G,
+    task: Cell<Task>,
+    command: Cell<Option<Task>>,
+    forced: Cell<bool>,
+    satisfaction: Cell<f64>,
+    hunger: Cell<f64>,
+    energy: Cell<f64>,
+    sleep_debt: Cell<u32>,
+    alert: Cell<u32>,
+    failed_work: Cell<u32>,
+    harvest: Cell<f64>,
+    history: RefCell<VecDeque<Task>>,
+    stock_offset: i64,
+    job: String,
+    personality: Personality,
+}
+
+impl<G> Character<G> {
+    const DISCONTENT: f64 = 0.3;
+    const FORCED_PENALTY: f64 = 0.2;
+    const CONTENT_GAIN: f64 = 0.05;
+    const WORK_EFFORT: f64 = 0.25;
+
+    fn new(graphics: G, personality: Personality, initial_task: Task) -> Self {
         Character {
-            graphics: node,
-            task: Cell::new(Task::Sleep),
+            graphics,
+            task: Cell::new(initial_task),
+            command: Cell::new(None),
+            forced: Cell::new(false),
+            satisfaction: Cell::new(1.0),
+            hunger: Cell::new(0.0),
+            energy: Cell::new(1.0),
+            sleep_debt: Cell::new(0),
+            alert: Cell::new(0),
+            failed_work: Cell::new(0),
+            harvest: Cell::new(1.0),
+            history: RefCell::new(VecDeque::new()),
+            stock_offset: 0,
+            job: String::from(Controller::DEFAULT_JOB),
             personality,
         }
     }
+
+    fn eat(&self) {
+        self.hunger.set(0.0);
+    }
+
+    fn force(&self, task: Task) {
+        self.task.set(task);
+        self.forced.set(true);
+        if let Some(last) = self.history.borrow_mut().back_mut() {
+            *last = task;
+        }
+    }
+
+    fn remember(&self, task: Task, limit: usize) {
+        let mut history = self.history.borrow_mut();
+        history.push_back(task);
+        while history.len() > limit {
+            history.pop_front();
+        }
+    }
+
+    fn tire(&self, debt_factor: f64) {
+        let effort = Self::WORK_EFFORT * (1.0 + self.sleep_debt.get() as f64 * debt_factor);
+        self.energy.set((self.energy.get() - effort).max(0.0));
+    }
+
+    fn account_night(&self, slept: bool) {
+        let debt = self.sleep_debt.get();
+        self.sleep_debt.set(if slept {
+            debt.saturating_sub(1)
+        } else {
+            debt + 1
+        });
+    }
+
+    fn rest(&self, recovery: f64) {
+        self.energy.set((self.energy.get() + recovery).min(1.0));
+    }
+
+    fn forget_failure(&self) {
+        self.failed_work
+            .set(self.failed_work.get().saturating_sub(1));
+    }
+
+    fn calm_down(&self) {
+        self.alert.set(self.alert.get().saturating_sub(1));
+    }
+
+    fn get_hungrier(&self, rate: f64) {
+        self.hunger.set(self.hunger.get() + rate);
+    }
+
+    fn settle(&self) {
+        let change = if self.forced.take() {
+            -Self::FORCED_PENALTY
+        } else {
+            Self::CONTENT_GAIN
+        };
+        self.satisfaction
+            .set((self.satisfaction.get() + change).clamp(0.0, 1.0));
+    }
 }
 
-#[derive(Clone, Copy)]
+impl Character {
+    fn snapshot(&self, index: usize) -> Dictionary {
+        let mut dict = Dictionary::new();
+        dict.set("index", index as i64);
+        dict.set("name", self.graphics.get_name());
+        dict.set("personality", self.personality.name());
+        dict.set("task", format!("{:?}", self.task.get()));
+        dict.set("hunger", self.hunger.get());
+        dict.set("satisfaction", self.satisfaction.get());
+        dict.set("energy", self.energy.get());
+        dict.set("sleep_debt", self.sleep_debt.get());
+        dict
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(test, derive(Default))]
 enum Task {
     Eat,
+    #[cfg_attr(test, default)]
     Sleep,
     Work,
+    Steal,
 }
 
-impl Character {
-    fn decide(&self, view: WorldView) -> Task {
+impl Task {
+    const FALLBACKS: [Task; 3] = [Task::Sleep, Task::Eat, Task::Work];
+
+    fn parse(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "eat" => Some(Task::Eat),
+            "sleep" => Some(Task::Sleep),
+            "work" => Some(Task::Work),
+            "steal" => Some(Task::Steal),
+            _ => None,
+        }
+    }
+}
+
+impl<G> Character<G> {
+    fn decide(&self, view: WorldView, excluded: &[Task]) -> (Task, &'static str) {
+        let decision = self.decide_freely(view);
+        if !excluded.contains(&decision.0) {
+            return decision;
+        }
+        Task::FALLBACKS
+            .into_iter()
+            .find(|task| !excluded.contains(task))
+            .map_or(decision, |task| {
+                (task, "retry: first choice failed → fallback")
+            })
+    }
+
+    fn decide_freely(&self, view: WorldView) -> (Task, &'static str) {
+        let view = WorldView {
+            apple_stock: view.apple_stock + self.stock_offset,
+            ..view
+        };
+        let (day_kind, stock, reserve) = (view.day_kind, view.apple_stock, view.eat_reserve);
+        let discouraged = self.failed_work.get() as f64 * view.work_aversion >= 1.0;
+        let decision = match (self.decide_by_personality(view), day_kind) {
+            (decision @ (Task::Sleep, _), _) | (decision, DayKind::Ordinary) => decision,
+            (_, DayKind::Gather) => (Task::Work, "gather day → work"),
+            (_, DayKind::Feast) => (Task::Eat, "feast day → eat"),
+        };
+        match (decision, reserve) {
+            ((Task::Eat, _), Some(reserve)) if stock as f64 <= reserve => {
+                (Task::Work, "stock below the eating reserve → work")
+            }
+            ((Task::Work, _), _) if discouraged => (Task::Sleep, "work keeps failing → sleep"),
+            _ => decision,
+        }
+    }
+
+    fn decide_by_personality(&self, view: WorldView) -> (Task, &'static str) {
         match self.personality {
             Personality::Greedy => match view.time.phase {
-                Phase::Predawn | Phase::Night => Task::Sleep,
+                _ if view.theft_window && view.apple_stock > 0 => {
+                    (Task::Steal, "greedy: nobody watching → steal")
+                }
+                Phase::Predawn | Phase::Night => (Task::Sleep, "greedy: night → sleep"),
                 _ => {
                     if view.apple_stock > 0 {
-                        Task::Eat
+                        (Task::Eat, "greedy: apples in stock → eat")
                     } else {
-                        Task::Work
+                        (Task::Work, "greedy: stockpile empty → work")
                     }
                 }
             },
-            Personality::Cooperative => match view.time.phase {
-                Phase::Predawn | Phase::Night => Task::Sleep,
-                Phase::Morning | Phase::Evening => Task::Work,
-                Phase::Midday => Task::Eat,
+            Personality::Lazy => match view.time.phase {
+                Phase::Predawn | Phase::Night => (Task::Sleep, "lazy: night → sleep"),
+                _ if view.apple_stock <= 0 => (Task::Work, "lazy: stockpile empty → work"),
+                Phase::Midday => (Task::Eat, "lazy: midday → eat"),
+                Phase::Morning | Phase::Evening => (Task::Sleep, "lazy: not hungry → sleep"),
+            },
+            Personality::Cooperative if self.alert.get() > 0 => match view.time.phase {
+                Phase::Predawn | Phase::Night => (Task::Sleep, "alert: night → sleep"),
+                _ => (Task::Work, "alert: theft nearby → work near the stockpile"),
+            },
+            Personality::Cooperative if self.satisfaction.get() < Self::DISCONTENT => {
+                match view.time.phase {
+                    Phase::Predawn | Phase::Night => (Task::Sleep, "discontent: night → sleep"),
+                    _ => {
+                        if view.apple_stock > 0 {
+                            (Task::Eat, "discontent: apples in stock → eat")
+                        } else {
+                            (Task::Sleep, "discontent: stockpile empty → sleep")
+                        }
+                    }
+                }
+            }
+            Personality::Cooperative => match (view.time.phase, view.goal_met) {
+                (Phase::Predawn | Phase::Night, _) => (Task::Sleep, "cooperative: night → sleep"),
+                (_, Some(false)) => (Task::Work, "cooperative: community goal unmet → work"),
+                (Phase::Morning | Phase::Evening, Some(true)) => {
+                    (Task::Sleep, "cooperative: community goal met → rest")
+                }
+                (Phase::Morning | Phase::Evening, None) => {
+                    (Task::Work, "cooperative: working hours → work")
+                }
+                (Phase::Midday, _) => (Task::Eat, "cooperative: midday → eat"),
             },
         }
     }
 }
 
+struct DecisionTrace {
+    character: usize,
+    phase: Phase,
+    chosen: Task,
+    reason: &'static str,
+}
+
+impl DecisionTrace {
+    fn to_dictionary(&self) -> Dictionary {
+        let mut dict = Dictionary::new();
+        dict.set("character", self.character as i64);
+        dict.set("phase", format!("{:?}", self.phase));
+        dict.set("chosen", format!("{:?}", self.chosen));
+        dict.set("reason", self.reason);
+        dict
+    }
+}
+
 #[derive(PartialEq, Debug, Clone, Copy)]
 enum Phase {
     Predawn,
@@ -81,7 +344,9 @@ enum Phase {
 }
 
 #[derive(PartialEq, Debug, Clone, Copy)]
+#[cfg_attr(test, derive(Default))]
 enum SubPhase {
+    #[cfg_attr(test, default)]
     Commence,
     Progress,
     Complete,
@@ -89,6 +354,8 @@ enum SubPhase {
 }
 
 impl SubPhase {
+    const COUNT: usize = 4;
+
     fn next(self) -> Self {
         match self {
             SubPhase::Commence => SubPhase::Progress,
@@ -97,38 +364,118 @@ impl SubPhase {
             SubPhase::Tempo => SubPhase::Commence,
         }
     }
+
+    fn index(self) -> usize {
+        match self {
+            SubPhase::Commence => 0,
+            SubPhase::Progress => 1,
+            SubPhase::Complete => 2,
+            SubPhase::Tempo => 3,
+        }
+    }
+
+    fn parse(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "commence" => Some(SubPhase::Commence),
+            "progress" => Some(SubPhase::Progress),
+            "complete" => Some(SubPhase::Complete),
+            "tempo" => Some(SubPhase::Tempo),
+            _ => None,
+        }
+    }
 }
 
+#[derive(PartialEq, Debug, Clone, Copy)]
+#[cfg_attr(test, derive(Default))]
 enum Season {
+    #[cfg_attr(test, default)]
     Summer,
     Winter,
 }
 
+impl Season {
+    fn name(&self) -> &'static str {
+        match self {
+            Season::Summer => "Summer",
+            Season::Winter => "Winter",
+        }
+    }
+
+    fn parse(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "summer" => Some(Season::Summer),
+            "winter" => Some(Season::Winter),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone)]
 struct GameTime {
     day: i64,
     phase: Phase,
     sub: SubPhase,
+    order: Rc<[Phase]>,
 }
 
 impl GameTime {
-    fn start() -> Self {
+    const DEFAULT_ORDER: [Phase; 5] = [
+        Phase::Predawn,
+        Phase::Morning,
+        Phase::Midday,
+        Phase::Evening,
+        Phase::Night,
+    ];
+
+    fn start(order: Rc<[Phase]>) -> Self {
         GameTime {
             day: 1,
-            phase: Phase::Predawn,
+            phase: order[0],
             sub: SubPhase::Tempo,
+            order,
+        }
+    }
+
+    fn position(&self, phase: Phase) -> Option<usize> {
+        self.order.iter().position(|p| *p == phase)
+    }
+
+    fn parse_phase(&self, name: &str) -> Result<Phase, String> {
+        let phase = Phase::parse(name).ok_or_else(|| format!("unknown phase {:?}", name))?;
+        match self.position(phase) {
+            Some(_) => Ok(phase),
+            None => Err(format!("{:?} is not in the phase order", phase)),
         }
     }
 
     fn next(&mut self) {
         self.sub = self.sub.next();
         if self.sub == SubPhase::Commence {
-            self.phase = self.phase.next();
-            if self.phase == Phase::Predawn {
+            let next = self
+                .position(self.phase)
+                .map_or(0, |i| (i + 1) % self.order.len());
+            self.phase = self.order[next];
+            if next == 0 {
                 self.day += 1;
             }
         }
     }
 
+    fn time_of_day(&self) -> f64 {
+        let phase = self.position(self.phase).unwrap_or(0);
+        let elapsed = phase * SubPhase::COUNT + self.sub.index();
+        elapsed as f64 / (self.order.len() * SubPhase::COUNT) as f64
+    }
+
+    fn next_at(&self, sub: SubPhase) -> GameTime {
+        let mut time = self.clone();
+        time.next();
+        while time.sub != sub {
+            time.next();
+        }
+        time
+    }
+
     fn season(&self) -> Season {
         if (self.day / 5) % 4 == 3 {
             Season::Winter
@@ -139,13 +486,24 @@ impl GameTime {
 }
 
 impl Phase {
-    fn next(&self) -> Self {
+    fn index(self) -> usize {
         match self {
-            Phase::Predawn => Phase::Morning,
-            Phase::Morning => Phase::Midday,
-            Phase::Midday => Phase::Evening,
-            Phase::Evening => Phase::Night,
-            Phase::Night => Phase::Predawn,
+            Phase::Predawn => 0,
+            Phase::Morning => 1,
+            Phase::Midday => 2,
+            Phase::Evening => 3,
+            Phase::Night => 4,
+        }
+    }
+
+    fn parse(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "predawn" => Some(Phase::Predawn),
+            "morning" => Some(Phase::Morning),
+            "midday" => Some(Phase::Midday),
+            "evening" => Some(Phase::Evening),
+            "night" => Some(Phase::Night),
+            _ => None,
         }
     }
 }
@@ -155,17 +513,145 @@ impl Phase {
 struct SampleChildren {
     #[export]
     parent: Option<Gd<Node2D>>,
+    #[export]
+    #[init(default = 1.0)]
+    maturity: f64,
+    picked: Vec<(Gd<Node2D>, Vector2)>,
 }
 
 impl SampleChildren {
-    fn pick(&self) -> Gd<Node2D> {
-        self.parent
-            .as_ref()
-            .unwrap()
-            .get_children()
-            .pick_random()
-            .unwrap()
-            .cast()
+    fn pick(&mut self) -> Option<Gd<Node2D>> {
+        let mut parent = self.parent.clone()?;
+        let (apple, _) = Branch::pick(&mut parent, &mut self.picked)?;
+        Some(apple)
+    }
+
+    fn regrow(&mut self) {
+        let Some(mut parent) = self.parent.clone() else {
+            return;
+        };
+        Branch::regrow(&mut parent, &mut self.picked);
+    }
+}
+
+trait Branch {
+    type Apple: Clone;
+
+    fn detach(&mut self) -> Option<Self::Apple>;
+    fn attach(&mut self, apple: Self::Apple);
+
+    fn pick(&mut self, picked: &mut Vec<Self::Apple>) -> Option<Self::Apple> {
+        let apple = self.detach()?;
+        picked.push(apple.clone());
+        Some(apple)
+    }
+
+    fn regrow(&mut self, picked: &mut Vec<Self::Apple>) {
+        for apple in picked.drain(..) {
+            self.attach(apple);
+        }
+    }
+}
+
+impl Branch for Gd<Node2D> {
+    type Apple = (Gd<Node2D>, Vector2);
+
+    fn detach(&mut self) -> Option<Self::Apple> {
+        let mut apple: Gd<Node2D> = self.get_children().pick_random()?.cast();
+        let (local, global) = (apple.get_position(), apple.get_global_position());
+        self.remove_child(apple.clone().upcast());
+        apple.set_position(global);
+        Some((apple, local))
+    }
+
+    fn attach(&mut self, (mut apple, local): Self::Apple) {
+        apple.set_position(local);
+        self.add_child(apple.upcast());
+    }
+}
+
+trait AppleSource {
+    fn node(&self) -> Gd<Node>;
+    fn valid(&self) -> bool;
+    fn pick(&self) -> Option<Gd<Node2D>>;
+    fn regrow(&self) {}
+
+    fn maturity(&self) -> f64 {
+        1.0
+    }
+
+    fn grow(&self, _amount: f64) {}
+}
+
+impl AppleSource for Gd<SampleChildren> {
+    fn node(&self) -> Gd<Node> {
+        self.clone().upcast()
+    }
+
+    fn valid(&self) -> bool {
+        self.is_instance_valid()
+    }
+
+    fn pick(&self) -> Option<Gd<Node2D>> {
+        self.clone().bind_mut().pick()
+    }
+
+    fn regrow(&self) {
+        self.clone().bind_mut().regrow()
+    }
+
+    fn maturity(&self) -> f64 {
+        self.bind().maturity.clamp(0.0, 1.0)
+    }
+
+    fn grow(&self, amount: f64) {
+        let mut source = self.clone();
+        let mut tree = source.bind_mut();
+        tree.maturity = (tree.maturity + amount).min(1.0);
+    }
+}
+
+struct ScriptedSource {
+    node: Gd<Node>,
+}
+
+impl AppleSource for ScriptedSource {
+    fn node(&self) -> Gd<Node> {
+        self.node.clone()
+    }
+
+    fn valid(&self) -> bool {
+        self.node.is_instance_valid()
+    }
+
+    fn pick(&self) -> Option<Gd<Node2D>> {
+        self.node
+            .clone()
+            .call("pick".into(), &[])
+            .try_to::<Gd<Node2D>>()
+            .ok()
+    }
+
+    fn regrow(&self) {
+        if self.node.has_method("regrow".into()) {
+            self.node.clone().call("regrow".into(), &[]);
+        }
+    }
+}
+
+fn apple_source(node: Gd<Node>) -> Option<Box<dyn AppleSource>> {
+    match node.try_cast::<SampleChildren>() {
+        Ok(tree) => Some(Box::new(tree)),
+        Err(node) if node.has_method("pick".into()) => Some(Box::new(ScriptedSource { node })),
+        Err(_) => None,
+    }
+}
+
+fn global_position(node: &Node2D) -> Vector2 {
+    if node.is_inside_tree() {
+        node.get_global_position()
+    } else {
+        node.get_position()
     }
 }
 
@@ -173,6 +659,49 @@ impl SampleChildren {
 enum Outcome {
     StatusQuo,
     Apples { delta: i64 },
+    Hoard { delta: i64 },
+    SpawnNode { scene_path: GString, at: Vector2 },
+}
+
+impl Outcome {
+    fn to_dictionary(&self) -> Dictionary {
+        let mut dict = Dictionary::new();
+        match self {
+            Outcome::StatusQuo => dict.set("kind", "status_quo"),
+            Outcome::Apples { delta } => {
+                dict.set("kind", "apples");
+                dict.set("delta", *delta);
+            }
+            Outcome::Hoard { delta } => {
+                dict.set("kind", "hoard");
+                dict.set("delta", *delta);
+            }
+            Outcome::SpawnNode { scene_path, at } => {
+                dict.set("kind", "spawn_node");
+                dict.set("scene_path", scene_path.clone());
+                dict.set("at", *at);
+            }
+        }
+        dict
+    }
+
+    fn from_dictionary(dict: &Dictionary) -> Option<Self> {
+        let kind = dict.get("kind")?.stringify().to_string();
+        match kind.as_str() {
+            "status_quo" => Some(Outcome::StatusQuo),
+            "apples" => Some(Outcome::Apples {
+                delta: dict.get("delta")?.try_to::<i64>().ok()?,
+            }),
+            "hoard" => Some(Outcome::Hoard {
+                delta: dict.get("delta")?.try_to::<i64>().ok()?,
+            }),
+            "spawn_node" => Some(Outcome::SpawnNode {
+                scene_path: dict.get("scene_path")?.try_to::<GString>().ok()?,
+                at: dict.get("at")?.try_to::<Vector2>().ok()?,
+            }),
+            _ => None,
+        }
+    }
 }
 
 impl Default for Outcome {
@@ -181,30 +710,48 @@ impl Default for Outcome {
     }
 }
 
+static NEXT_CHANNEL: AtomicU64 = AtomicU64::new(1);
+
 #[derive(Default, Clone)]
 struct OutcomeChannel {
+    seq: u64,
     cell: Rc<Vec<Outcome>>,
     consumed: Rc<Cell<usize>>,
     available: Rc<Cell<usize>>,
+    timer: Rc<Cell<f64>>,
 }
 
 impl OutcomeChannel {
     fn new(events: Vec<Outcome>, start: usize) -> Self {
         OutcomeChannel {
+            seq: NEXT_CHANNEL.fetch_add(1, atomic::Ordering::Relaxed),
             cell: Rc::new(events),
             consumed: Rc::new(Cell::new(0)),
             available: Rc::new(Cell::new(start)),
+            timer: Rc::new(Cell::new(0.0)),
         }
     }
 
-    fn check(self) -> (Option<Outcome>, Option<Self>) {
+    fn after(self, seconds: f64) -> Self {
+        self.timer.set(seconds);
+        self
+    }
+
+    fn check(self, delta: f64) -> (Option<Outcome>, Option<Self>) {
+        let timer = self.timer.get();
+        if timer > 0.0 {
+            self.timer.set(timer - delta);
+            if timer <= delta {
+                self.fire();
+            }
+        }
         if self.consumed.get() >= self.cell.len() {
             (None, None)
         } else if self.consumed.get() < self.available.get() {
             let i = self.consumed.get();
             self.consumed.set(i + 1);
             (Some(self.cell.get(i).unwrap().clone()), Some(self))
-        } else if self.available.get() < self.cell.len() {
+        } else if self.fired() < self.cell.len() {
             (None, Some(self))
         } else {
             (None, None)
@@ -215,6 +762,68 @@ impl OutcomeChannel {
         self.available.set(self.available.get() + 1)
     }
 
+    fn pending(&self) -> usize {
+        self.cell.len().saturating_sub(self.consumed.get())
+    }
+
+    fn fired(&self) -> usize {
+        self.available.get().min(self.cell.len())
+    }
+
+    fn carried(&self) -> i64 {
+        self.cell[self.cell.len() - self.pending()..]
+            .iter()
+            .map(|outcome| match outcome {
+                Outcome::Apples { delta } if *delta > 0 => *delta,
+                _ => 0,
+            })
+            .sum()
+    }
+
+    fn cancel(&self) {
+        self.consumed.set(self.cell.len())
+    }
+
+    fn land(&self) {
+        self.available.set(self.cell.len());
+        self.timer.set(0.0);
+    }
+
+    fn to_dictionary(&self) -> Dictionary {
+        let outcomes: Array<Dictionary> = self.cell.iter().map(Outcome::to_dictionary).collect();
+        let mut dict = Dictionary::new();
+        dict.set("outcomes", outcomes);
+        dict.set("consumed", self.consumed.get() as i64);
+        dict.set("available", self.available.get() as i64);
+        dict.set("timer", self.timer.get());
+        dict.set("seq", self.seq as i64);
+        dict
+    }
+
+    fn from_dictionary(dict: &Dictionary) -> Option<Self> {
+        let count = |key: &str| usize::try_from(dict.get(key)?.try_to::<i64>().ok()?).ok();
+        let outcomes = dict
+            .get("outcomes")?
+            .try_to::<Array<Dictionary>>()
+            .ok()?
+            .iter_shared()
+            .map(|outcome| Outcome::from_dictionary(&outcome))
+            .collect::<Option<Vec<_>>>()?;
+        let mut channel = OutcomeChannel::new(outcomes, count("available")?)
+            .after(dict.get("timer")?.try_to::<f64>().ok()?);
+        channel.consumed.set(count("consumed")?);
+        if let Some(seq) = dict.get("seq") {
+            channel = channel.with_seq(seq.try_to::<i64>().ok()? as u64);
+        }
+        Some(channel)
+    }
+
+    fn with_seq(mut self, seq: u64) -> Self {
+        NEXT_CHANNEL.fetch_max(seq + 1, atomic::Ordering::Relaxed);
+        self.seq = seq;
+        self
+    }
+
     fn immediate(outcome: Outcome) -> Self {
         OutcomeChannel::new(vec![outcome], 1)
     }
@@ -237,11 +846,12 @@ struct OutcomeMux {
 }
 
 impl OutcomeMux {
-    fn tick(self) -> (Vec<Outcome>, Option<Self>) {
+    fn tick(mut self, delta: f64) -> (Vec<Outcome>, Option<Self>) {
         let mut done: Vec<Outcome> = vec![];
         let mut remaining: Vec<OutcomeChannel> = vec![];
+        self.channels.sort_by_key(|channel| channel.seq);
         self.channels.into_iter().for_each(|channel| {
-            let (outcome, rest) = channel.check();
+            let (outcome, rest) = channel.check(delta);
             outcome.map(|outcome| done.push(outcome));
             rest.map(|rest| remaining.push(rest));
         });
@@ -257,6 +867,18 @@ impl OutcomeMux {
         )
     }
 
+    fn settle(self) -> Vec<Outcome> {
+        self.channels.iter().for_each(OutcomeChannel::land);
+        let mut outcomes = vec![];
+        let mut mux = Some(self);
+        while let Some(current) = mux {
+            let (due, next) = current.tick(f64::INFINITY);
+            outcomes.extend(due);
+            mux = next;
+        }
+        outcomes
+    }
+
     fn from(channels: impl IntoIterator<Item = OutcomeChannel>) -> Self {
         OutcomeMux {
             channels: channels.into_iter().collect(),
@@ -265,279 +887,3883 @@ impl OutcomeMux {
 }
 
 enum Item {
-    Wait { seconds: f64 },
+    Wait { seconds: f64, sub_phases: u32 },
     Play(OutcomeMux),
 }
 
 impl Item {
+    fn wait(seconds: f64) -> Self {
+        Item::Wait {
+            seconds,
+            sub_phases: 1,
+        }
+    }
+
+    fn sub_phases(&self) -> u32 {
+        match self {
+            Item::Wait { sub_phases, .. } => *sub_phases,
+            Item::Play(_) => 1,
+        }
+    }
+
+    fn push(queue: &mut VecDeque<Item>, item: Item) {
+        if let (
+            Some(Item::Wait {
+                seconds,
+                sub_phases,
+            }),
+            Item::Wait {
+                seconds: more,
+                sub_phases: spanned,
+            },
+        ) = (queue.back_mut(), &item)
+        {
+            *seconds += more;
+            *sub_phases += spanned;
+            return;
+        }
+        queue.push_back(item);
+    }
+
+    fn to_dictionary(&self) -> Dictionary {
+        let mut dict = Dictionary::new();
+        match self {
+            Item::Wait {
+                seconds,
+                sub_phases,
+            } => {
+                dict.set("kind", "wait");
+                dict.set("seconds", *seconds);
+                dict.set("sub_phases", *sub_phases as i64);
+            }
+            Item::Play(mux) => {
+                let channels: Array<Dictionary> = mux
+                    .channels
+                    .iter()
+                    .map(OutcomeChannel::to_dictionary)
+                    .collect();
+                dict.set("kind", "play");
+                dict.set("channels", channels);
+            }
+        }
+        dict
+    }
+
+    fn from_dictionary(dict: &Dictionary) -> Option<Self> {
+        match dict.get("kind")?.stringify().to_string().as_str() {
+            "wait" => Some(Item::Wait {
+                seconds: dict.get("seconds")?.try_to::<f64>().ok()?,
+                sub_phases: match dict.get("sub_phases") {
+                    Some(count) => u32::try_from(count.try_to::<i64>().ok()?).ok()?.max(1),
+                    None => 1,
+                },
+            }),
+            "play" => Some(Item::Play(OutcomeMux::from(parse_channels(
+                &dict.get("channels")?,
+            )?))),
+            _ => None,
+        }
+    }
+
+    fn settle(self) -> (Vec<Outcome>, u32) {
+        let sub_phases = self.sub_phases();
+        match self {
+            Item::Wait { .. } => (vec![], sub_phases),
+            Item::Play(mux) => (mux.settle(), sub_phases),
+        }
+    }
+
     fn tick(self, delta: f64) -> (Vec<Outcome>, Option<Self>) {
         match self {
-            Item::Wait { seconds } => {
+            Item::Wait {
+                seconds,
+                sub_phases,
+            } => {
                 if seconds >= delta {
                     (
                         vec![],
                         Some(Item::Wait {
                             seconds: seconds - delta,
+                            sub_phases,
                         }),
                     )
                 } else {
                     (vec![], None)
                 }
             }
-            Item::Play(outcomes) => match outcomes.tick() {
-                (done, left) => (done, left.map(Item::Play)),
-            },
+            Item::Play(outcomes) => {
+                let (done, left) = outcomes.tick(delta);
+                (done, left.map(Item::Play))
+            }
         }
     }
 }
 
-#[derive(GodotClass)]
-#[class(base=Node, no_init)]
-struct Controller {
-    time: GameTime,
-    queue: VecDeque<Item>,
-    time_indicator: Gd<Control>,
-    stockpile: Gd<Node2D>,
-    apple_tree: Gd<SampleChildren>,
-    characters: Vec<Character>,
-    apples: i64,
-    base: Base<Node>,
+struct Rng {
+    state: Cell<u64>,
 }
 
-#[derive(GodotClass)]
-#[class(base=Node, init)]
-struct Cyst {
-    #[export]
-    time_indicator: Option<Gd<Control>>,
-    #[export]
-    stockpile: Option<Gd<Node2D>>,
-    #[export]
-    apple_tree: Option<Gd<SampleChildren>>,
-    base: Base<Node>,
+impl Rng {
+    fn seeded(seed: u64) -> Self {
+        Rng {
+            state: Cell::new(seed),
+        }
+    }
+
+    fn state(&self) -> u64 {
+        self.state.get()
+    }
+
+    fn set_state(&self, state: u64) {
+        self.state.set(state)
+    }
+
+    fn next_u64(&self) -> u64 {
+        let state = self.state.get().wrapping_add(0x9E37_79B9_7F4A_7C15);
+        self.state.set(state);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    fn below(&self, bound: usize) -> usize {
+        (self.next_u64() % bound as u64) as usize
+    }
+
+    fn chance(&self, probability: f64) -> bool {
+        let roll = (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64;
+        roll < probability
+    }
+
+    fn shuffle<T>(&self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.below(i + 1));
+        }
+    }
 }
 
-impl Cyst {
-    fn parts(&mut self) -> Option<(Gd<Control>, Gd<Node2D>, Gd<SampleChildren>)> {
-        self.time_indicator.take().and_then(|time| {
-            self.stockpile.take().and_then(|stock| {
-                self.apple_tree
-                    .take()
-                    .and_then(|apples| Option::Some((time, stock, apples)))
-            })
-        })
+#[derive(Default)]
+struct Stockpile {
+    stored_on: VecDeque<i64>,
+}
+
+impl Stockpile {
+    fn from_days(days: impl IntoIterator<Item = i64>) -> Self {
+        Stockpile {
+            stored_on: days.into_iter().collect(),
+        }
+    }
+
+    fn count(&self) -> i64 {
+        self.stored_on.len() as i64
+    }
+
+    fn store(&mut self, day: i64, amount: i64) {
+        for _ in 0..amount {
+            self.stored_on.push_back(day);
+        }
+    }
+
+    fn take(&mut self, amount: i64) {
+        for _ in 0..amount {
+            self.stored_on.pop_front();
+        }
+    }
+
+    fn spoil(&mut self, today: i64, shelf_life: i64) -> i64 {
+        let before = self.count();
+        while let Some(&day) = self.stored_on.front() {
+            if today - day <= shelf_life {
+                break;
+            }
+            self.stored_on.pop_front();
+        }
+        before - self.count()
     }
 }
 
-#[derive(GodotClass)]
-#[class(base=Node2D, init)]
-struct Traveler {
-    velocity: Vector2,
-    target: Vector2,
-    signal: OutcomeChannel,
-    base: Base<Node2D>,
+trait OutcomeSink {
+    fn write(&mut self, resource: &str, amount: i64);
 }
 
-impl Traveler {
-    fn new(speed: f32, result: OutcomeChannel, from: &Node2D, to: &Node2D) -> Gd<Self> {
-        let start = from.get_global_position();
-        let end = to.get_global_position();
-        let velocity = (end - start).normalized() * speed;
-        let mut traveler = Gd::from_init_fn(|base| Traveler {
-            velocity,
-            signal: result,
-            target: end,
-            base,
-        });
-        traveler.set_global_position(start);
-        traveler
+impl OutcomeSink for Box<dyn OutcomeSink> {
+    fn write(&mut self, resource: &str, amount: i64) {
+        (**self).write(resource, amount)
     }
+}
 
-    fn load_child(&mut self, scene: &str) {
-        let scene: Gd<PackedScene> = load(scene);
-        let node = scene.instantiate_as::<Node>();
-        self.base_mut().add_child(node);
+struct PropertySink {
+    node: Gd<Node2D>,
+    apples_property: StringName,
+}
+
+impl OutcomeSink for PropertySink {
+    fn write(&mut self, resource: &str, amount: i64) {
+        let property = match resource {
+            "apples" => self.apples_property.clone(),
+            other => other.into(),
+        };
+        self.node.set(property, Variant::from(amount));
     }
 }
 
-#[godot_api]
-impl INode2D for Traveler {
-    fn process(&mut self, delta: f64) {
-        let displacement = delta as f32 * self.velocity;
-        let new_pos = self
-            .base()
-            .get_global_position()
-            .move_toward(self.target, displacement.length());
-        self.base_mut().set_global_position(new_pos);
-        if new_pos == self.target {
-            self.signal.fire();
-            self.base_mut().queue_free()
+#[derive(Clone, Copy)]
+#[cfg_attr(test, derive(Default))]
+enum StackLayout {
+    #[cfg_attr(test, default)]
+    Grid,
+    Pyramid,
+}
+
+impl StackLayout {
+    const WIDTH: usize = 5;
+    const SPACING: f32 = 14.0;
+
+    fn parse(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "grid" => Some(StackLayout::Grid),
+            "pyramid" => Some(StackLayout::Pyramid),
+            _ => None,
         }
     }
+
+    fn offset(self, index: usize) -> Vector2 {
+        let (row, col, indent) = match self {
+            StackLayout::Grid => (index / Self::WIDTH, index % Self::WIDTH, 0.0),
+            StackLayout::Pyramid => {
+                let mut row = 0;
+                let mut rest = index;
+                while rest >= (Self::WIDTH - row % Self::WIDTH) {
+                    rest -= Self::WIDTH - row % Self::WIDTH;
+                    row += 1;
+                }
+                (row, rest, (row % Self::WIDTH) as f32 * 0.5)
+            }
+        };
+        Vector2::new(
+            (col as f32 + indent) * Self::SPACING,
+            -(row as f32) * Self::SPACING,
+        )
+    }
 }
 
-impl Controller {
-    fn new(cyst: &mut Cyst) -> Option<Gd<Self>> {
-        cyst.parts().map(|(time, stock, tree)| {
-            Gd::from_init_fn(|base| Self {
-                queue: VecDeque::with_capacity(4),
-                time: GameTime::start(),
-                characters: vec![],
-                apples: 0,
-                base,
-                time_indicator: time,
-                stockpile: stock,
-                apple_tree: tree,
-            })
-        })
+trait Pile {
+    fn len(&self) -> usize;
+    fn push(&mut self, offset: Vector2);
+    fn pop(&mut self);
+
+    fn restack(&mut self, layout: StackLayout, count: usize) {
+        while self.len() > count {
+            self.pop();
+        }
+        while self.len() < count {
+            self.push(layout.offset(self.len()));
+        }
     }
+}
 
-    fn fulfill(&self, character: &Character, task: Task) -> OutcomeChannel {
-        match task {
-            Task::Eat => self.eat_apple(character),
-            Task::Sleep => OutcomeChannel::immediate_noop(),
-            Task::Work => self.pick_apple(character),
+struct StackedApples {
+    parent: Gd<Node2D>,
+    sprites: Vec<Gd<Node2D>>,
+}
+
+impl StackedApples {
+    fn new(parent: Gd<Node2D>) -> Self {
+        StackedApples {
+            parent,
+            sprites: vec![],
         }
     }
+}
 
-    fn finish(&self, character: &Character, task: Task) -> OutcomeChannel {
-        match task {
-            Task::Eat => OutcomeChannel::immediate_noop(),
-            Task::Sleep => OutcomeChannel::immediate_noop(),
-            Task::Work => match self.time.season() {
-                Season::Summer => self.store_apple(character),
-                Season::Winter => OutcomeChannel::immediate_noop(),
-            },
+impl Pile for StackedApples {
+    fn len(&self) -> usize {
+        self.sprites.len()
+    }
+
+    fn push(&mut self, offset: Vector2) {
+        let scene: Gd<PackedScene> = load("res://apple.tscn");
+        let mut sprite = scene.instantiate_as::<Node2D>();
+        sprite.set_position(offset);
+        self.parent.add_child(sprite.clone().upcast());
+        self.sprites.push(sprite);
+    }
+
+    fn pop(&mut self) {
+        if let Some(mut sprite) = self.sprites.pop() {
+            sprite.queue_free();
         }
     }
+}
 
-    fn apply(&mut self, o: &Outcome) {
-        match o {
-            Outcome::StatusQuo => (),
-            Outcome::Apples { delta } => self.apples += delta,
+struct StockDisplay<S = Box<dyn OutcomeSink>, P = StackedApples> {
+    sink: S,
+    stack: P,
+    dirty: bool,
+    low: bool,
+}
+
+impl<S: OutcomeSink, P: Pile> StockDisplay<S, P> {
+    fn new(sink: S, stack: P) -> Self {
+        StockDisplay {
+            sink,
+            stack,
+            dirty: false,
+            low: false,
         }
-        self.stockpile
-            .set("apples".into(), Variant::from(self.apples));
     }
 
-    fn spawn_sibling(&self, sib: Gd<impl Inherits<Node>>) {
-        self.base().get_parent().unwrap().add_child(sib.upcast())
+    fn flush(&mut self, count: i64, config: &Config) -> Option<&'static str> {
+        if !std::mem::take(&mut self.dirty) {
+            return None;
+        }
+        self.sync(count, config)
     }
 
-    fn send_apple(
-        &self,
-        speed: f32,
-        ch: OutcomeChannel,
-        from: &Node2D,
-        to: &Node2D,
-    ) -> OutcomeChannel {
-        let mut traveler = Traveler::new(speed, ch.clone(), from, to);
-        traveler.bind_mut().load_child("res://apple.tscn");
-        self.spawn_sibling(traveler);
-        ch
+    fn sync(&mut self, count: i64, config: &Config) -> Option<&'static str> {
+        self.sink.write("apples", count);
+        let visible = count.clamp(0, config.max_visible_apples) as usize;
+        self.stack.restack(config.stack_layout, visible);
+        let threshold = config.low_stock_threshold?;
+        let low = count < threshold;
+        let changed = low != std::mem::replace(&mut self.low, low);
+        changed.then_some(if low { "low_stock" } else { "stock_recovered" })
     }
+}
 
-    fn pick_apple(&self, character: &Character) -> OutcomeChannel {
-        let spawn = self.apple_tree.bind().pick();
-        self.send_apple(
-            400.0,
-            OutcomeChannel::delayed_noop(),
-            &spawn,
-            &character.graphics,
-        )
+#[derive(GodotClass)]
+#[class(base=Node, no_init)]
+struct Controller {
+    time: GameTime,
+    queue: VecDeque<Item>,
+    background: Vec<OutcomeChannel>,
+    latest_traveler: Cell<Option<InstanceId>>,
+    travelers: RefCell<Vec<InstanceId>>,
+    pending_spawns: RefCell<VecDeque<Gd<Traveler>>>,
+    pending_signals: RefCell<Vec<(&'static str, Vec<Variant>)>>,
+    time_indicator: Gd<Control>,
+    stockpile: Gd<Node2D>,
+    hoard: Option<Gd<Node2D>>,
+    hoarded: i64,
+    lost_sources: RefCell<HashSet<String>>,
+    sources: HashMap<String, Box<dyn AppleSource>>,
+    characters: Vec<Character>,
+    apples: Stockpile,
+    display: StockDisplay,
+    decision_traces: Vec<DecisionTrace>,
+    headless: bool,
+    outcome_transform: Option<Callable>,
+    rng: Rng,
+    idle: bool,
+    shown_time: Option<(Phase, i64)>,
+    decision_windows: u64,
+    stalled_windows: usize,
+    daily_totals: Vec<(i64, i64)>,
+    last_birth: Option<i64>,
+    config: Config,
+    base: Base<Node>,
+}
+
+#[derive(Clone, Copy)]
+#[cfg_attr(test, derive(Default))]
+struct YieldCurve {
+    base: i64,
+    falloff: f64,
+    floor: i64,
+}
+
+impl YieldCurve {
+    fn at(&self, worker: usize) -> i64 {
+        let falling = self.base as f64 - self.falloff * worker as f64;
+        (falling.round() as i64).max(self.floor)
     }
+}
 
-    fn eat_apple(&self, character: &Character) -> OutcomeChannel {
-        self.send_apple(
-            1000.0,
-            OutcomeChannel::new(vec![Outcome::Apples { delta: -1 }, Outcome::StatusQuo], 1),
-            &self.stockpile,
-            &character.graphics,
-        )
+#[derive(Clone, Copy, PartialEq)]
+enum FinishEffect {
+    Nothing,
+    Store,
+    Rest,
+}
+
+impl FinishEffect {
+    fn parse(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "nothing" => Some(FinishEffect::Nothing),
+            "store" => Some(FinishEffect::Store),
+            "rest" => Some(FinishEffect::Rest),
+            _ => None,
+        }
     }
+}
 
-    fn store_apple(&self, character: &Character) -> OutcomeChannel {
-        self.send_apple(
-            1000.0,
-            OutcomeChannel::delayed(Outcome::Apples { delta: 1 }),
-            &character.graphics,
-            &self.stockpile,
-        )
+#[derive(Clone, Copy)]
+#[cfg_attr(test, derive(Default))]
+enum ApplyOrder {
+    #[cfg_attr(test, default)]
+    Auto,
+    InsertionOrder,
+    NegativeFirst,
+    PositiveFirst,
+}
+
+impl ApplyOrder {
+    fn parse(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "auto" => Some(ApplyOrder::Auto),
+            "insertion_order" => Some(ApplyOrder::InsertionOrder),
+            "negative_first" => Some(ApplyOrder::NegativeFirst),
+            "positive_first" => Some(ApplyOrder::PositiveFirst),
+            _ => None,
+        }
     }
+}
 
-    fn view(&self) -> WorldView {
-        WorldView {
-            time: &self.time,
-            apple_stock: self.apples,
+fn parse_finish_rule(rule: &str) -> Option<(Task, Season, FinishEffect)> {
+    let mut parts = rule.split(':').map(str::trim);
+    let task = Task::parse(parts.next()?)?;
+    let season = Season::parse(parts.next()?)?;
+    let effect = FinishEffect::parse(parts.next()?)?;
+    parts.next().is_none().then_some((task, season, effect))
+}
+
+fn parse_speech_line(entry: &str) -> Option<(Personality, Task, String)> {
+    let mut parts = entry.splitn(3, ':');
+    let personality = Personality::parse(parts.next()?.trim())?;
+    let task = Task::parse(parts.next()?.trim())?;
+    let line = parts.next()?.trim();
+    (!line.is_empty()).then(|| (personality, task, String::from(line)))
+}
+
+fn parse_time_slot(slot: &str) -> Option<(Phase, SubPhase)> {
+    let (phase, sub) = slot.split_once(':')?;
+    Some((Phase::parse(phase.trim())?, SubPhase::parse(sub.trim())?))
+}
+
+fn parse_channels(channels: &Variant) -> Option<Vec<OutcomeChannel>> {
+    channels
+        .try_to::<Array<Dictionary>>()
+        .ok()?
+        .iter_shared()
+        .map(|channel| OutcomeChannel::from_dictionary(&channel))
+        .collect()
+}
+
+struct SavedTraveler {
+    channel: u64,
+    start: Vector2,
+    position: Vector2,
+    target: Vector2,
+    velocity: Vector2,
+    delay: f64,
+    tolerance: f32,
+    tag: String,
+}
+
+impl SavedTraveler {
+    fn parse(dict: &Dictionary) -> Option<Self> {
+        let vector = |key: &str| dict.get(key)?.try_to::<Vector2>().ok();
+        let number = |key: &str| dict.get(key)?.try_to::<f64>().ok();
+        Some(SavedTraveler {
+            channel: dict.get("channel")?.try_to::<i64>().ok()? as u64,
+            start: vector("start")?,
+            position: vector("position")?,
+            target: vector("target")?,
+            velocity: vector("velocity")?,
+            delay: number("delay")?,
+            tolerance: number("tolerance")? as f32,
+            tag: dict.get("tag")?.stringify().to_string(),
+        })
+    }
+}
+
+struct SavedState {
+    day: i64,
+    phase: Phase,
+    sub: SubPhase,
+    apples: Vec<i64>,
+    rng: u64,
+    queue: Vec<Item>,
+    background: Vec<OutcomeChannel>,
+    travelers: Option<Vec<SavedTraveler>>,
+}
+
+impl SavedState {
+    fn parse(state: &Dictionary) -> Option<Self> {
+        let field = |key: &str| state.get(key).map(|value| value.stringify().to_string());
+        Some(SavedState {
+            day: state.get("day")?.try_to::<i64>().ok()?,
+            phase: Phase::parse(&field("phase")?)?,
+            sub: SubPhase::parse(&field("sub")?)?,
+            apples: state
+                .get("apples")?
+                .try_to::<PackedInt64Array>()
+                .ok()?
+                .to_vec(),
+            rng: state.get("rng")?.try_to::<i64>().ok()? as u64,
+            queue: match state.get("queue") {
+                Some(queue) => queue
+                    .try_to::<Array<Dictionary>>()
+                    .ok()?
+                    .iter_shared()
+                    .map(|item| Item::from_dictionary(&item))
+                    .collect::<Option<_>>()?,
+                None => vec![],
+            },
+            background: match state.get("background") {
+                Some(background) => parse_channels(&background)?,
+                None => vec![],
+            },
+            travelers: match state.get("travelers") {
+                Some(travelers) => Some(
+                    travelers
+                        .try_to::<Array<Dictionary>>()
+                        .ok()?
+                        .iter_shared()
+                        .map(|traveler| SavedTraveler::parse(&traveler))
+                        .collect::<Option<_>>()?,
+                ),
+                None => None,
+            },
+        })
+    }
+}
+
+#[derive(Clone)]
+#[cfg_attr(test, derive(Default))]
+struct Config {
+    stack_layout: StackLayout,
+    max_visible_apples: i64,
+    trace_decisions: bool,
+    shelf_life: Option<i64>,
+    character_group: String,
+    work_yield: YieldCurve,
+    eat_duration: f64,
+    decision_sub: SubPhase,
+    cleanup_sub: SubPhase,
+    finish_rules: Vec<(Task, Season, FinishEffect)>,
+    max_characters: Option<usize>,
+    meal_times: Vec<(Phase, SubPhase)>,
+    arrival_tolerance: f32,
+    personality_weights: PersonalityWeights,
+    spawns_per_frame: Option<usize>,
+    stockpile_property: String,
+    difficulty_per_day: f64,
+    feast_cycle: Option<i64>,
+    seed: u64,
+    phase_order: Rc<[Phase]>,
+    alert_radius: f32,
+    alert_duration: u32,
+    initial_task: Task,
+    feed_hungriest_first: bool,
+    starving_hunger: Option<f64>,
+    shuffle_actions: bool,
+    night_curfew: bool,
+    decision_interval: u64,
+    apply_order: ApplyOrder,
+    task_history: usize,
+    rest_recovery: f64,
+    travel_duration: Option<f64>,
+    trip_durations: HashMap<String, f64>,
+    strike_threshold: Option<f64>,
+    strike_chance: f64,
+    max_outcome_delta: Option<i64>,
+    min_frame_delta: f64,
+    max_frame_delta: f64,
+    windfall_chance: f64,
+    windfall_apples: (usize, usize),
+    task_icons: [Option<String>; 3],
+    icon_duration: f64,
+    stockpile_capacity: Option<i64>,
+    eat_ratio: f64,
+    speech_lines: Vec<(Personality, Task, String)>,
+    speech_rate: f64,
+    work_aversion: f64,
+    rest_quality: [f64; 5],
+    catastrophe_chance: f64,
+    frost_loss: i64,
+    blight_loss: usize,
+    phase_tints: Option<[Color; 5]>,
+    daily_tax: f64,
+    break_deadlocks: bool,
+    price_reference: f64,
+    price_elasticity: f64,
+    dispatch_retries: usize,
+    record_timeseries: bool,
+    sleep_debt_factor: f64,
+    birth_scene: Option<String>,
+    birth_season: Season,
+    birth_hunger: f64,
+    birth_energy: f64,
+    birth_interval: i64,
+    track_targets: bool,
+    appetites: [i64; 3],
+    traveler_brightness: Option<[f32; 5]>,
+    tree_growth: f64,
+    personality_by_index: bool,
+    theft_phases: Vec<Phase>,
+    work_success: f64,
+    eat_success: f64,
+    low_stock_threshold: Option<i64>,
+    community_goal: Option<i64>,
+    decision_jitter: i64,
+    share_surplus: bool,
+    share_radius: f32,
+    trace_limit: Option<usize>,
+    maturity_exponent: f64,
+    min_harvest_yield: i64,
+}
+
+impl Config {
+    fn day_kind(&self, day: i64) -> DayKind {
+        match self.feast_cycle {
+            None => DayKind::Ordinary,
+            Some(cycle) if day % cycle == 0 => DayKind::Feast,
+            Some(_) => DayKind::Gather,
         }
     }
 
-    fn character_actions(&self) -> Item {
-        let mut actions = vec![];
-        for c in self.characters.iter() {
-            let task = c.decide(self.view());
-            c.task.set(task);
-            actions.push(self.fulfill(c, task));
+    fn task_icon(&self, task: Task) -> Option<&str> {
+        let [eat, sleep, work] = &self.task_icons;
+        match task {
+            Task::Eat => eat.as_deref(),
+            Task::Sleep => sleep.as_deref(),
+            Task::Work => work.as_deref(),
+            Task::Steal => None,
         }
-        Item::Play(OutcomeMux::from(actions))
     }
 
-    fn character_cleanup(&self) -> Item {
-        let mut cleanups = vec![];
-        for c in self.characters.iter() {
-            let task = c.task.get();
-            cleanups.push(self.finish(c, task));
+    fn eat_reserve(&self) -> Option<f64> {
+        let capacity = self.stockpile_capacity?;
+        (self.eat_ratio > 0.0).then_some(capacity as f64 * self.eat_ratio)
+    }
+
+    fn rest_quality(&self, phase: Phase) -> f64 {
+        self.rest_quality[phase.index()]
+    }
+
+    fn apple_price(&self, stock: i64) -> f64 {
+        (self.price_reference / stock.max(1) as f64).powf(self.price_elasticity)
+    }
+
+    fn appetite(&self, personality: Personality) -> i64 {
+        let [cooperative, greedy, lazy] = self.appetites;
+        match personality {
+            Personality::Cooperative => cooperative,
+            Personality::Greedy => greedy,
+            Personality::Lazy => lazy,
         }
-        Item::Play(OutcomeMux::from(cleanups))
     }
 
-    fn schedule_item(&self) -> Item {
-        match self.time.sub {
-            SubPhase::Commence => self.character_actions(),
-            SubPhase::Complete => self.character_cleanup(),
-            _ => Item::Wait { seconds: 0.5 },
+    fn harvest_yield(&self, full: i64, maturity: f64) -> i64 {
+        if maturity <= 0.0 {
+            return 0;
         }
+        let scaled = full as f64 * maturity.powf(self.maturity_exponent);
+        (scaled.round() as i64).max(self.min_harvest_yield.min(full))
     }
-}
 
-#[godot_api]
-impl INode for Controller {
-    fn process(&mut self, delta: f64) {
-        let current = self.queue.pop_front();
-        match current {
-            None => self.queue.push_back(self.schedule_item()),
-            Some(current) => {
-                let (outcomes, next) = current.tick(delta);
-                for outcome in &outcomes {
-                    self.apply(outcome)
-                }
-                match next {
-                    Some(next) => self.queue.push_front(next),
-                    None => self.time.next(),
-                }
-                self.time_indicator.call(
-                    "set_time".into(),
-                    &[
-                        Variant::from(format!("{:?}", self.time.phase)),
-                        Variant::from(format!("{}", self.time.day)),
-                    ],
-                );
-            }
+    fn personality_for(&self, index: usize, name_hash: impl FnOnce() -> u32) -> Personality {
+        let key = if self.personality_by_index {
+            index as u32
+        } else {
+            name_hash()
+        };
+        self.personality_weights.assign(key)
+    }
+
+    fn view<'a>(&self, time: &'a GameTime, apple_stock: i64) -> WorldView<'a> {
+        WorldView {
+            time,
+            apple_stock,
+            day_kind: self.day_kind(time.day),
+            eat_reserve: self.eat_reserve(),
+            work_aversion: self.work_aversion,
+            theft_window: self.theft_phases.contains(&time.phase),
+            goal_met: self.community_goal.map(|goal| apple_stock >= goal),
         }
     }
 
-    fn enter_tree(&mut self) {
-        self.base()
-            .get_tree()
-            .unwrap()
-            .get_nodes_in_group("characters".into())
-            .iter_shared()
-            .for_each(|node| self.characters.push(Character::new(node.cast())));
+    fn curfew(&self, phase: Phase) -> bool {
+        self.night_curfew && matches!(phase, Phase::Night | Phase::Predawn)
+    }
+
+    fn strikes<G>(&self, character: &Character<G>, rng: &Rng) -> bool {
+        self.strike_threshold
+            .is_some_and(|threshold| character.satisfaction.get() < threshold)
+            && rng.chance(self.strike_chance)
+    }
+
+    fn hunger_rate(&self, day: i64) -> f64 {
+        1.0 + day as f64 * self.difficulty_per_day
+    }
+
+    fn finish_effect(&self, task: Task, season: Season) -> FinishEffect {
+        self.finish_rules
+            .iter()
+            .find(|(t, s, _)| *t == task && *s == season)
+            .map_or(FinishEffect::Nothing, |(_, _, effect)| *effect)
     }
 }
 
-#[godot_api]
-impl INode for Cyst {
-    fn enter_tree(&mut self) {
-        Controller::new(self).map(|controller| self.base_mut().add_child(controller.upcast()));
+#[derive(GodotClass)]
+#[class(base=Node, init)]
+struct Cyst {
+    #[export]
+    time_indicator: Option<Gd<Control>>,
+    #[export]
+    stockpile: Option<Gd<Node2D>>,
+    #[export]
+    apple_tree: Option<Gd<Node>>,
+    #[export]
+    greedy_hoard: Option<Gd<Node2D>>,
+    #[export]
+    #[init(default = GString::from("grid"))]
+    stack_layout: GString,
+    #[export]
+    #[init(default = 20)]
+    max_visible_apples: i64,
+    #[export]
+    trace_decisions: bool,
+    #[export]
+    shelf_life: i64,
+    #[export]
+    #[init(default = GString::from("characters"))]
+    character_group: GString,
+    #[export]
+    #[init(default = 1)]
+    work_yield: i64,
+    #[export]
+    work_yield_falloff: f64,
+    #[export]
+    min_work_yield: i64,
+    #[export]
+    eat_duration: f64,
+    #[export]
+    #[init(default = GString::from("commence"))]
+    decision_subphase: GString,
+    #[export]
+    #[init(default = GString::from("complete"))]
+    cleanup_subphase: GString,
+    #[export]
+    #[init(default = PackedStringArray::from(&[GString::from("work:summer:store")][..]))]
+    finish_rules: PackedStringArray,
+    #[export]
+    max_characters: i64,
+    #[export]
+    meal_times: PackedStringArray,
+    #[export]
+    #[init(default = 0.5)]
+    arrival_tolerance: f64,
+    #[export]
+    #[init(default = 3)]
+    cooperative_weight: i64,
+    #[export]
+    #[init(default = 1)]
+    greedy_weight: i64,
+    #[export]
+    lazy_weight: i64,
+    #[export]
+    spawns_per_frame: i64,
+    #[export]
+    #[init(default = GString::from("apples"))]
+    stockpile_property: GString,
+    #[export]
+    difficulty_per_day: f64,
+    #[export]
+    feast_cycle: i64,
+    #[export]
+    seed: i64,
+    #[export]
+    phase_order: PackedStringArray,
+    #[export]
+    #[init(default = 300.0)]
+    alert_radius: f64,
+    #[export]
+    #[init(default = 8)]
+    alert_duration: i64,
+    #[export]
+    #[init(default = GString::from("sleep"))]
+    initial_task: GString,
+    #[export]
+    feed_hungriest_first: bool,
+    #[export]
+    starving_hunger: f64,
+    #[export]
+    shuffle_actions: bool,
+    #[export]
+    night_curfew: bool,
+    #[export]
+    #[init(default = 1)]
+    decision_interval: i64,
+    #[export]
+    #[init(default = GString::from("auto"))]
+    apply_order: GString,
+    #[export]
+    #[init(default = 8)]
+    task_history: i64,
+    #[export]
+    #[init(default = 0.5)]
+    rest_recovery: f64,
+    #[export]
+    travel_duration: f64,
+    #[export]
+    trip_durations: Dictionary,
+    #[export]
+    strike_threshold: f64,
+    #[export]
+    #[init(default = 0.5)]
+    strike_chance: f64,
+    #[export]
+    #[init(default = 10_000)]
+    max_outcome_delta: i64,
+    #[export]
+    min_frame_delta: f64,
+    #[export]
+    #[init(default = 0.25)]
+    max_frame_delta: f64,
+    #[export]
+    windfall_chance: f64,
+    #[export]
+    #[init(default = 1)]
+    min_windfall: i64,
+    #[export]
+    #[init(default = 3)]
+    max_windfall: i64,
+    #[export]
+    eat_icon: GString,
+    #[export]
+    sleep_icon: GString,
+    #[export]
+    work_icon: GString,
+    #[export]
+    #[init(default = 1.0)]
+    icon_duration: f64,
+    #[export]
+    stockpile_capacity: i64,
+    #[export]
+    eat_ratio: f64,
+    #[export]
+    speech_lines: PackedStringArray,
+    #[export]
+    #[init(default = 0.1)]
+    speech_rate: f64,
+    #[export]
+    work_aversion: f64,
+    #[export]
+    rest_quality: PackedFloat64Array,
+    #[export]
+    catastrophe_chance: f64,
+    #[export]
+    #[init(default = 10)]
+    frost_loss: i64,
+    #[export]
+    blight_loss: i64,
+    #[export]
+    phase_tints: PackedColorArray,
+    #[export]
+    daily_tax: f64,
+    #[export]
+    break_deadlocks: bool,
+    #[export]
+    #[init(default = 20)]
+    price_reference: i64,
+    #[export]
+    price_elasticity: f64,
+    #[export]
+    dispatch_retries: i64,
+    #[export]
+    record_timeseries: bool,
+    #[export]
+    sleep_debt_factor: f64,
+    #[export]
+    birth_scene: GString,
+    #[export]
+    #[init(default = GString::from("summer"))]
+    birth_season: GString,
+    #[export]
+    #[init(default = 1.0)]
+    birth_hunger: f64,
+    #[export]
+    #[init(default = 0.5)]
+    birth_energy: f64,
+    #[export]
+    #[init(default = 5)]
+    birth_interval: i64,
+    #[export]
+    track_targets: bool,
+    #[export]
+    #[init(default = 1)]
+    cooperative_appetite: i64,
+    #[export]
+    #[init(default = 1)]
+    greedy_appetite: i64,
+    #[export]
+    #[init(default = 1)]
+    lazy_appetite: i64,
+    #[export]
+    traveler_brightness: PackedFloat32Array,
+    #[export]
+    #[init(default = 0.1)]
+    tree_growth: f64,
+    #[export]
+    personality_by_index: bool,
+    #[export]
+    theft_phases: PackedStringArray,
+    #[export]
+    #[init(default = 1.0)]
+    work_success: f64,
+    #[export]
+    #[init(default = 1.0)]
+    eat_success: f64,
+    #[export]
+    low_stock_threshold: i64,
+    #[export]
+    community_goal: i64,
+    #[export]
+    decision_jitter: i64,
+    #[export]
+    share_surplus: bool,
+    #[export]
+    #[init(default = 300.0)]
+    share_radius: f64,
+    #[export]
+    #[init(default = 256)]
+    trace_limit: i64,
+    #[export]
+    #[init(default = 1.0)]
+    maturity_exponent: f64,
+    #[export]
+    min_harvest_yield: i64,
+    base: Base<Node>,
+}
+
+impl Cyst {
+    fn config(&self) -> Config {
+        let layout = self.stack_layout.to_string();
+        let decision_sub = self.decision_sub();
+        let cleanup_sub = self.cleanup_sub(decision_sub);
+        Config {
+            stack_layout: StackLayout::parse(&layout).unwrap_or_else(|| {
+                godot_warn!("unknown stack layout {:?}, using grid", layout);
+                StackLayout::Grid
+            }),
+            max_visible_apples: self.max_visible_apples.max(0),
+            trace_decisions: self.trace_decisions,
+            shelf_life: Some(self.shelf_life).filter(|days| *days > 0),
+            character_group: self.character_group.to_string(),
+            work_yield: YieldCurve {
+                base: self.work_yield,
+                falloff: self.work_yield_falloff.max(0.0),
+                floor: self.min_work_yield.min(self.work_yield),
+            },
+            eat_duration: self.eat_duration.max(0.0),
+            decision_sub,
+            cleanup_sub,
+            finish_rules: self
+                .finish_rules
+                .to_vec()
+                .iter()
+                .filter_map(|rule| {
+                    let rule = rule.to_string();
+                    let parsed = parse_finish_rule(&rule);
+                    if parsed.is_none() {
+                        godot_warn!("ignoring malformed finish rule {:?}", rule);
+                    }
+                    parsed
+                })
+                .collect(),
+            max_characters: usize::try_from(self.max_characters)
+                .ok()
+                .filter(|max| *max > 0),
+            meal_times: self.meal_times(decision_sub, cleanup_sub),
+            arrival_tolerance: self.arrival_tolerance.max(0.0) as f32,
+            personality_weights: self.personality_weights(),
+            spawns_per_frame: usize::try_from(self.spawns_per_frame)
+                .ok()
+                .filter(|limit| *limit > 0),
+            stockpile_property: if self.stockpile_property.is_empty() {
+                godot_warn!("stockpile_property is empty, using apples");
+                String::from("apples")
+            } else {
+                self.stockpile_property.to_string()
+            },
+            difficulty_per_day: self.difficulty_per_day.max(0.0),
+            feast_cycle: Some(self.feast_cycle).filter(|days| *days > 0),
+            seed: self.seed as u64,
+            phase_order: self.phase_order(),
+            alert_radius: self.alert_radius.max(0.0) as f32,
+            alert_duration: u32::try_from(self.alert_duration).unwrap_or(0),
+            initial_task: Task::parse(&self.initial_task.to_string()).unwrap_or_else(|| {
+                godot_warn!("unknown initial task {}, using sleep", self.initial_task);
+                Task::Sleep
+            }),
+            feed_hungriest_first: self.feed_hungriest_first,
+            starving_hunger: Some(self.starving_hunger).filter(|hunger| *hunger > 0.0),
+            shuffle_actions: self.shuffle_actions,
+            night_curfew: self.night_curfew,
+            decision_interval: self.decision_interval.max(1) as u64,
+            apply_order: ApplyOrder::parse(&self.apply_order.to_string()).unwrap_or_else(|| {
+                godot_warn!("unknown apply order {}, using auto", self.apply_order);
+                ApplyOrder::Auto
+            }),
+            task_history: usize::try_from(self.task_history).unwrap_or(0),
+            rest_recovery: self.rest_recovery.max(0.0),
+            travel_duration: Some(self.travel_duration).filter(|seconds| *seconds > 0.0),
+            trip_durations: self.trip_durations(),
+            strike_threshold: Some(self.strike_threshold).filter(|threshold| *threshold > 0.0),
+            strike_chance: self.strike_chance.clamp(0.0, 1.0),
+            max_outcome_delta: Some(self.max_outcome_delta).filter(|max| *max > 0),
+            min_frame_delta: self.min_frame_delta.max(0.0),
+            max_frame_delta: Some(self.max_frame_delta)
+                .filter(|delta| *delta > 0.0)
+                .map_or(f64::INFINITY, |delta| delta.max(self.min_frame_delta)),
+            windfall_chance: self.windfall_chance.clamp(0.0, 1.0),
+            windfall_apples: {
+                let min = usize::try_from(self.min_windfall).unwrap_or(0);
+                (
+                    min,
+                    usize::try_from(self.max_windfall).unwrap_or(0).max(min),
+                )
+            },
+            task_icons: [&self.eat_icon, &self.sleep_icon, &self.work_icon]
+                .map(|path| Some(path.to_string()).filter(|path| !path.is_empty())),
+            icon_duration: self.icon_duration.max(0.0),
+            stockpile_capacity: Some(self.stockpile_capacity).filter(|capacity| *capacity > 0),
+            eat_ratio: self.eat_ratio.clamp(0.0, 1.0),
+            speech_lines: self
+                .speech_lines
+                .to_vec()
+                .iter()
+                .filter_map(|entry| {
+                    let entry = entry.to_string();
+                    let parsed = parse_speech_line(&entry);
+                    if parsed.is_none() {
+                        godot_warn!("ignoring malformed speech line {:?}", entry);
+                    }
+                    parsed
+                })
+                .collect(),
+            speech_rate: self.speech_rate.clamp(0.0, 1.0),
+            work_aversion: self.work_aversion.max(0.0),
+            rest_quality: self.rest_quality(),
+            catastrophe_chance: self.catastrophe_chance.clamp(0.0, 1.0),
+            frost_loss: self.frost_loss.max(0),
+            blight_loss: usize::try_from(self.blight_loss).unwrap_or(0),
+            phase_tints: self.phase_tints(),
+            daily_tax: self.daily_tax.clamp(0.0, 1.0),
+            break_deadlocks: self.break_deadlocks,
+            price_reference: self.price_reference.max(1) as f64,
+            price_elasticity: self.price_elasticity.max(0.0),
+            dispatch_retries: usize::try_from(self.dispatch_retries).unwrap_or(0),
+            record_timeseries: self.record_timeseries,
+            sleep_debt_factor: self.sleep_debt_factor.max(0.0),
+            birth_scene: Some(self.birth_scene.to_string()).filter(|path| !path.is_empty()),
+            birth_season: Season::parse(&self.birth_season.to_string()).unwrap_or_else(|| {
+                godot_warn!("unknown birth season {}, using summer", self.birth_season);
+                Season::Summer
+            }),
+            birth_hunger: self.birth_hunger,
+            birth_energy: self.birth_energy,
+            birth_interval: self.birth_interval.max(1),
+            track_targets: self.track_targets,
+            appetites: [
+                self.cooperative_appetite,
+                self.greedy_appetite,
+                self.lazy_appetite,
+            ]
+            .map(|appetite| appetite.max(1)),
+            traveler_brightness: self.traveler_brightness(),
+            tree_growth: self.tree_growth.max(0.0),
+            personality_by_index: self.personality_by_index,
+            theft_phases: self
+                .theft_phases
+                .to_vec()
+                .iter()
+                .filter_map(|name| {
+                    let name = name.to_string();
+                    let phase = Phase::parse(&name);
+                    if phase.is_none() {
+                        godot_warn!("ignoring unknown theft phase {:?}", name);
+                    }
+                    phase
+                })
+                .collect(),
+            work_success: self.work_success.clamp(0.0, 1.0),
+            eat_success: self.eat_success.clamp(0.0, 1.0),
+            low_stock_threshold: Some(self.low_stock_threshold).filter(|threshold| *threshold > 0),
+            community_goal: Some(self.community_goal).filter(|goal| *goal > 0),
+            decision_jitter: self.decision_jitter.max(0),
+            share_surplus: self.share_surplus,
+            share_radius: self.share_radius.max(0.0) as f32,
+            trace_limit: usize::try_from(self.trace_limit)
+                .ok()
+                .filter(|limit| *limit > 0),
+            maturity_exponent: self.maturity_exponent.max(0.0),
+            min_harvest_yield: self.min_harvest_yield.max(0),
+        }
+    }
+
+    fn phase_order(&self) -> Rc<[Phase]> {
+        let names: Vec<String> = self
+            .phase_order
+            .to_vec()
+            .iter()
+            .map(GString::to_string)
+            .collect();
+        let order: Option<Vec<Phase>> = names.iter().map(|name| Phase::parse(name)).collect();
+        match order {
+            _ if names.is_empty() => Rc::from(&GameTime::DEFAULT_ORDER[..]),
+            Some(order)
+                if order
+                    .iter()
+                    .enumerate()
+                    .all(|(i, phase)| !order[..i].contains(phase)) =>
+            {
+                order.into()
+            }
+            _ => {
+                godot_warn!("invalid phase order {:?}, using the default", names);
+                Rc::from(&GameTime::DEFAULT_ORDER[..])
+            }
+        }
+    }
+
+    fn trip_durations(&self) -> HashMap<String, f64> {
+        self.trip_durations
+            .iter_shared()
+            .filter_map(|(kind, seconds)| {
+                let parsed = seconds
+                    .try_to::<f64>()
+                    .or_else(|_| seconds.try_to::<i64>().map(|s| s as f64))
+                    .ok()
+                    .filter(|s| *s > 0.0);
+                if parsed.is_none() {
+                    godot_warn!("ignoring trip duration {} for {}", seconds, kind);
+                }
+                parsed.map(|s| (kind.stringify().to_string(), s))
+            })
+            .collect()
+    }
+
+    fn rest_quality(&self) -> [f64; 5] {
+        let curve = self.rest_quality.to_vec();
+        match <[f64; 5]>::try_from(curve.as_slice()) {
+            _ if curve.is_empty() => [1.0; 5],
+            Ok(curve) => curve.map(|quality| quality.max(0.0)),
+            Err(_) => {
+                godot_warn!("rest_quality needs one value per phase, using a flat curve");
+                [1.0; 5]
+            }
+        }
+    }
+
+    fn phase_tints(&self) -> Option<[Color; 5]> {
+        let tints = self.phase_tints.to_vec();
+        match <[Color; 5]>::try_from(tints.as_slice()) {
+            _ if tints.is_empty() => None,
+            Ok(tints) => Some(tints),
+            Err(_) => {
+                godot_warn!("phase_tints needs one color per phase, not tinting characters");
+                None
+            }
+        }
+    }
+
+    fn traveler_brightness(&self) -> Option<[f32; 5]> {
+        let levels = self.traveler_brightness.to_vec();
+        match <[f32; 5]>::try_from(levels.as_slice()) {
+            _ if levels.is_empty() => None,
+            Ok(levels) => Some(levels.map(|level| level.max(0.0))),
+            Err(_) => {
+                godot_warn!("traveler_brightness needs one value per phase, ignoring it");
+                None
+            }
+        }
+    }
+
+    fn personality_weights(&self) -> PersonalityWeights {
+        let weight = |w: i64| u32::try_from(w).unwrap_or(0);
+        let weights = PersonalityWeights {
+            cooperative: weight(self.cooperative_weight),
+            greedy: weight(self.greedy_weight),
+            lazy: weight(self.lazy_weight),
+        };
+        if weights.cooperative == 0 && weights.greedy == 0 && weights.lazy == 0 {
+            godot_warn!("all personality weights are zero, using the default mix");
+            return PersonalityWeights::LEGACY;
+        }
+        weights
+    }
+
+    fn decision_sub(&self) -> SubPhase {
+        let name = self.decision_subphase.to_string();
+        SubPhase::parse(&name).unwrap_or_else(|| {
+            godot_warn!("unknown decision sub-phase {:?}, using commence", name);
+            SubPhase::Commence
+        })
+    }
+
+    fn meal_times(&self, decision: SubPhase, cleanup: SubPhase) -> Vec<(Phase, SubPhase)> {
+        self.meal_times
+            .to_vec()
+            .iter()
+            .filter_map(|slot| {
+                let slot = slot.to_string();
+                match parse_time_slot(&slot) {
+                    None => godot_warn!("ignoring malformed meal time {:?}", slot),
+                    Some((_, sub)) if sub == decision || sub == cleanup => godot_warn!(
+                        "ignoring meal time {:?}, its sub-phase is taken by decisions or cleanup",
+                        slot
+                    ),
+                    parsed => return parsed,
+                }
+                None
+            })
+            .collect()
+    }
+
+    fn cleanup_sub(&self, decision: SubPhase) -> SubPhase {
+        let name = self.cleanup_subphase.to_string();
+        let fallback = decision.next().next();
+        match SubPhase::parse(&name) {
+            Some(sub) if sub == decision => {
+                godot_warn!(
+                    "cleanup cannot share the decision sub-phase, using {:?}",
+                    fallback
+                );
+                fallback
+            }
+            Some(sub) => sub,
+            None => {
+                godot_warn!("unknown cleanup sub-phase {:?}, using {:?}", name, fallback);
+                fallback
+            }
+        }
+    }
+
+    fn parts(&self) -> Option<(Gd<Control>, Gd<Node2D>, Gd<Node>)> {
+        Some((
+            self.time_indicator.clone()?,
+            self.stockpile.clone()?,
+            self.apple_tree.clone()?,
+        ))
+    }
+
+    fn wiring_errors(
+        time: &Gd<Control>,
+        stock: &Gd<Node2D>,
+        tree: &Gd<Node>,
+        property: &str,
+    ) -> Vec<String> {
+        let mut errors = vec![];
+        if apple_source(tree.clone()).is_none() {
+            errors.push(format!(
+                "apple_tree {} is not a SampleChildren and has no pick() method",
+                tree.get_path()
+            ));
+        }
+        if !time.has_method("set_time".into()) {
+            errors.push(format!(
+                "time_indicator {} has no set_time(phase, day) method",
+                time.get_path()
+            ));
+        }
+        let wanted = GString::from(property);
+        let has_property = stock.get_property_list().iter_shared().any(|entry| {
+            entry
+                .get("name")
+                .is_some_and(|name| name.stringify() == wanted)
+        });
+        if !has_property {
+            errors.push(format!(
+                "stockpile {} has no {} property",
+                stock.get_path(),
+                property
+            ));
+        }
+        errors
+    }
+}
+
+#[derive(GodotClass)]
+#[class(base=Node2D, init)]
+struct Traveler {
+    start: Vector2,
+    velocity: Vector2,
+    target: Vector2,
+    signal: OutcomeChannel,
+    delay: f64,
+    tolerance: f32,
+    follow: Option<Gd<Node2D>>,
+    tag: StringName,
+    base: Base<Node2D>,
+}
+
+impl Traveler {
+    fn new(speed: f32, result: OutcomeChannel, from: &Node2D, to: &Node2D) -> Gd<Self> {
+        let start = global_position(from);
+        let end = global_position(to);
+        let velocity = (end - start).normalized() * speed;
+        let mut traveler = Gd::from_init_fn(|base| Traveler {
+            start,
+            velocity,
+            signal: result,
+            target: end,
+            delay: 0.0,
+            tolerance: 0.0,
+            follow: None,
+            tag: StringName::default(),
+            base,
+        });
+        traveler.set_global_position(start);
+        traveler
+    }
+
+    fn restore(saved: &SavedTraveler, signal: OutcomeChannel) -> Gd<Self> {
+        let mut traveler = Gd::from_init_fn(|base| Traveler {
+            start: saved.start,
+            velocity: saved.velocity,
+            signal,
+            target: saved.target,
+            delay: 0.0,
+            tolerance: saved.tolerance,
+            follow: None,
+            tag: saved.tag.as_str().into(),
+            base,
+        });
+        traveler.set_global_position(saved.position);
+        traveler
+    }
+
+    fn to_dictionary(&self) -> Dictionary {
+        let mut dict = Dictionary::new();
+        dict.set("channel", self.signal.seq as i64);
+        dict.set("start", self.start);
+        dict.set("position", global_position(&self.base()));
+        dict.set("target", self.target);
+        dict.set("velocity", self.velocity);
+        dict.set("delay", self.delay);
+        dict.set("tolerance", self.tolerance);
+        dict.set("tag", self.tag.clone());
+        dict
+    }
+
+    fn load_child(&mut self, scene: &str, brightness: Option<f32>) {
+        let scene: Gd<PackedScene> = load(scene);
+        let node = scene.instantiate_as::<Node>();
+        if let (Some(level), Ok(mut item)) = (brightness, node.clone().try_cast::<CanvasItem>()) {
+            item.set_modulate(Color::from_rgb(level, level, level));
+        }
+        self.base_mut().add_child(node);
+    }
+
+    fn hold(&mut self, seconds: f64) {
+        self.delay = seconds;
+        self.base_mut().set_visible(seconds <= 0.0);
+    }
+
+    fn progress(&self) -> f32 {
+        let total = self.start.distance_to(self.target);
+        if total <= 0.0 {
+            return 1.0;
+        }
+        let left = global_position(&self.base()).distance_to(self.target);
+        (1.0 - left / total).clamp(0.0, 1.0)
+    }
+
+    fn abort(&mut self) -> i64 {
+        let delivered = (self.progress() * self.signal.carried() as f32).floor() as i64;
+        self.signal.cancel();
+        self.base_mut().queue_free();
+        delivered
+    }
+}
+
+#[godot_api]
+impl INode2D for Traveler {
+    fn process(&mut self, delta: f64) {
+        if self.delay > 0.0 {
+            self.delay -= delta;
+            if self.delay <= 0.0 {
+                self.base_mut().set_visible(true);
+            }
+            return;
+        }
+        match self.follow.as_ref().filter(|node| node.is_instance_valid()) {
+            Some(node) => self.target = global_position(node),
+            None => self.follow = None,
+        }
+        let displacement = delta as f32 * self.velocity;
+        let new_pos = self
+            .base()
+            .get_global_position()
+            .move_toward(self.target, displacement.length());
+        self.base_mut().set_global_position(new_pos);
+        if new_pos.distance_to(self.target) <= self.tolerance {
+            let target = self.target;
+            self.base_mut().set_global_position(target);
+            self.signal.fire();
+            self.base_mut().queue_free()
+        }
+    }
+}
+
+impl Controller {
+    const GIFT_STAGGER: f64 = 0.15;
+    const SUB_PHASE_WAIT: f64 = 0.5;
+    const DEFAULT_JOB: &str = "work";
+    const ICON_OFFSET: Vector2 = Vector2::new(0.0, -24.0);
+
+    fn new(cyst: &Cyst) -> Option<Gd<Self>> {
+        let config = cyst.config();
+        let apples_property = config.stockpile_property.clone();
+        let seed = config.seed;
+        let phase_order = config.phase_order.clone();
+        let hoard = cyst.greedy_hoard.clone();
+        let parts = cyst.parts().filter(|(time, stock, tree)| {
+            let errors = Cyst::wiring_errors(time, stock, tree, &apples_property);
+            for error in &errors {
+                godot_error!("Cyst is miswired: {}", error);
+            }
+            errors.is_empty()
+        });
+        parts.map(|(time, stock, tree)| {
+            Gd::from_init_fn(|base| Self {
+                queue: VecDeque::with_capacity(4),
+                background: vec![],
+                latest_traveler: Cell::new(None),
+                travelers: RefCell::new(vec![]),
+                pending_spawns: RefCell::new(VecDeque::new()),
+                pending_signals: RefCell::new(vec![]),
+                time: GameTime::start(phase_order),
+                characters: vec![],
+                apples: Stockpile::default(),
+                display: StockDisplay::new(
+                    Box::new(PropertySink {
+                        node: stock.clone(),
+                        apples_property: apples_property.as_str().into(),
+                    }),
+                    StackedApples::new(stock.clone()),
+                ),
+                decision_traces: vec![],
+                headless: false,
+                outcome_transform: None,
+                rng: Rng::seeded(seed),
+                idle: false,
+                shown_time: None,
+                decision_windows: 0,
+                stalled_windows: 0,
+                daily_totals: vec![],
+                last_birth: None,
+                config,
+                base,
+                time_indicator: time,
+                stockpile: stock,
+                hoard,
+                hoarded: 0,
+                lost_sources: RefCell::new(HashSet::new()),
+                sources: HashMap::from([(
+                    String::from(Self::DEFAULT_JOB),
+                    apple_source(tree).unwrap(),
+                )]),
+            })
+        })
+    }
+
+    fn fulfill(&self, character: &Character, task: Task, serving: i64) -> Option<OutcomeChannel> {
+        match task {
+            Task::Sleep => Some(OutcomeChannel::immediate_noop()),
+            _ if self.curfew() => None,
+            Task::Eat if !self.succeeds(self.config.eat_success) => {
+                Some(OutcomeChannel::immediate_noop())
+            }
+            Task::Eat => {
+                character.eat();
+                Some(self.eat_apple(character, serving))
+            }
+            Task::Work => {
+                let channel = self.pick_apple(character)?;
+                if !self.succeeds(self.config.work_success) {
+                    character.harvest.set(0.0);
+                }
+                Some(channel)
+            }
+            Task::Steal => {
+                character.eat();
+                self.witness_theft(character);
+                Some(self.eat_apple(character, serving))
+            }
+        }
+    }
+
+    fn succeeds(&self, chance: f64) -> bool {
+        chance >= 1.0 || self.rng.chance(chance)
+    }
+
+    fn finish(&self, character: &Character, task: Task, worker: usize) -> OutcomeChannel {
+        match self.config.finish_effect(task, self.time.season()) {
+            FinishEffect::Nothing => OutcomeChannel::immediate_noop(),
+            FinishEffect::Store => {
+                let full = self.config.work_yield.at(worker);
+                match self.config.harvest_yield(full, character.harvest.get()) {
+                    amount if amount <= 0 => OutcomeChannel::immediate_noop(),
+                    amount => self.store_apple(character, amount),
+                }
+            }
+            FinishEffect::Rest => {
+                character
+                    .rest(self.config.rest_recovery * self.config.rest_quality(self.time.phase));
+                OutcomeChannel::immediate_noop()
+            }
+        }
+    }
+
+    fn transform(&mut self, o: &Outcome) -> Outcome {
+        let Some(transform) = self.outcome_transform.clone() else {
+            return o.clone();
+        };
+        let result = {
+            let _guard = self.base_mut();
+            transform.callv(varray![o.to_dictionary()])
+        };
+        match result
+            .try_to::<Dictionary>()
+            .ok()
+            .and_then(|dict| Outcome::from_dictionary(&dict))
+        {
+            Some(transformed) => transformed,
+            None => {
+                godot_warn!("outcome transform returned {}, applying unchanged", result);
+                o.clone()
+            }
+        }
+    }
+
+    fn apply(&mut self, o: &Outcome) {
+        let o = self.transform(o);
+        let o = &self.clamp(o);
+        let before = self.apples.count();
+        match o {
+            Outcome::StatusQuo => return,
+            Outcome::Apples { delta } if *delta >= 0 => self.store(*delta),
+            Outcome::Apples { delta } => self.apples.take(-delta),
+            Outcome::Hoard { delta } => return self.hoard_apples(*delta),
+            Outcome::SpawnNode { scene_path, at } => return self.spawn_node(scene_path, *at),
+        }
+        self.display.dirty = true;
+        let apples = self.apples.count();
+        let delta = apples - before;
+        self.emit_event("apples_changed", dict! {"delta": delta, "apples": apples});
+    }
+
+    fn clamp(&self, o: Outcome) -> Outcome {
+        match (o, self.config.max_outcome_delta) {
+            (Outcome::Apples { delta }, Some(max)) if delta.abs() > max => {
+                godot_warn!("clamping an apple outcome of {} to ±{}", delta, max);
+                Outcome::Apples {
+                    delta: delta.clamp(-max, max),
+                }
+            }
+            (o, _) => o,
+        }
+    }
+
+    fn apply_all(&mut self, mut outcomes: Vec<Outcome>) {
+        let delta = |o: &Outcome| match o {
+            Outcome::Apples { delta } => *delta,
+            _ => 0,
+        };
+        match self.config.apply_order {
+            ApplyOrder::Auto if self.config.stockpile_capacity.is_some() => {
+                outcomes.sort_by_key(|o| delta(o) >= 0)
+            }
+            ApplyOrder::Auto | ApplyOrder::InsertionOrder => (),
+            ApplyOrder::NegativeFirst => outcomes.sort_by_key(|o| delta(o) >= 0),
+            ApplyOrder::PositiveFirst => outcomes.sort_by_key(|o| delta(o) <= 0),
+        }
+        for outcome in &outcomes {
+            self.apply(outcome)
+        }
+    }
+
+    fn spawn_node(&self, scene_path: &GString, at: Vector2) {
+        if self.headless {
+            return;
+        }
+        let scene = match try_load::<PackedScene>(scene_path.clone()) {
+            Ok(scene) => scene,
+            Err(err) => {
+                godot_error!("cannot spawn {}: {}", scene_path, err);
+                return;
+            }
+        };
+        match scene.try_instantiate_as::<Node2D>() {
+            Some(mut node) => {
+                node.set_global_position(at);
+                self.spawn_sibling(node);
+            }
+            None => godot_error!("cannot spawn {}: root is not a Node2D", scene_path),
+        }
+    }
+
+    fn store(&mut self, amount: i64) {
+        let stored = match self.config.stockpile_capacity {
+            Some(capacity) => amount.min((capacity - self.apples.count()).max(0)),
+            None => amount,
+        };
+        self.apples.store(self.time.day, stored);
+        for _ in stored..amount {
+            if !self.config.share_surplus || !self.share_apple() {
+                break;
+            }
+        }
+    }
+
+    fn hoard_apples(&mut self, delta: i64) {
+        self.hoarded += delta;
+        if let Some(hoard) = self.hoard.as_mut() {
+            hoard.set(
+                self.config.stockpile_property.as_str().into(),
+                Variant::from(self.hoarded),
+            );
+        }
+    }
+
+    fn flush_stockpile(&mut self) {
+        let count = self.apples.count();
+        let changed = self.display.flush(count, &self.config);
+        self.announce_stock(changed, count);
+    }
+
+    fn sync_stockpile(&mut self) {
+        let count = self.apples.count();
+        let changed = self.display.sync(count, &self.config);
+        self.announce_stock(changed, count);
+    }
+
+    fn announce_stock(&self, changed: Option<&'static str>, count: i64) {
+        if let Some(signal) = changed {
+            self.emit(signal, &[Variant::from(count)]);
+            self.emit_event(signal, dict! {"current": count});
+        }
+    }
+
+    fn advance_time(&mut self) {
+        let (day, phase) = (self.time.day, self.time.phase);
+        self.time.next();
+        self.characters.iter().for_each(Character::calm_down);
+        if self.time.phase != phase {
+            let rate = self.config.hunger_rate(self.time.day);
+            self.characters.iter().for_each(|c| c.get_hungrier(rate));
+            self.characters.iter().for_each(Character::forget_failure);
+            if self.config.night_curfew && self.time.phase == Phase::Night {
+                self.recall_travelers();
+            }
+        }
+        if self.time.day != day {
+            if self.config.record_timeseries {
+                self.daily_totals.push((day, self.apples.count()));
+            }
+            for source in self.sources.values().filter(|source| source.valid()) {
+                source.regrow();
+                source.grow(self.config.tree_growth);
+            }
+            if let Some(shelf_life) = self.config.shelf_life {
+                if self.apples.spoil(self.time.day, shelf_life) > 0 {
+                    self.display.dirty = true;
+                }
+            }
+            if self.config.windfall_chance > 0.0 && self.rng.chance(self.config.windfall_chance) {
+                self.windfall();
+            }
+            let tax = (self.apples.count() as f64 * self.config.daily_tax).floor() as i64;
+            if tax > 0 {
+                self.apply(&Outcome::Apples { delta: -tax });
+            }
+            self.try_birth();
+            let chance = self.config.catastrophe_chance;
+            if self.time.season() == Season::Winter && chance > 0.0 && self.rng.chance(chance) {
+                self.catastrophe();
+            }
+        }
+    }
+
+    fn try_birth(&mut self) {
+        let Some(scene_path) = self.config.birth_scene.clone() else {
+            return;
+        };
+        let recent = self
+            .last_birth
+            .is_some_and(|day| self.time.day - day < self.config.birth_interval);
+        let crowded = self
+            .config
+            .max_characters
+            .is_some_and(|max| self.characters.len() >= max);
+        if self.headless || recent || crowded || self.time.season() != self.config.birth_season {
+            return;
+        }
+        let parents: Vec<&Character> = self
+            .characters
+            .iter()
+            .filter(|c| {
+                c.hunger.get() <= self.config.birth_hunger
+                    && c.energy.get() >= self.config.birth_energy
+            })
+            .take(2)
+            .collect();
+        let [parent, _] = parents[..] else {
+            return;
+        };
+        let at = parent.graphics.get_global_position();
+        let scene = match try_load::<PackedScene>(scene_path.as_str()) {
+            Ok(scene) => scene,
+            Err(err) => {
+                godot_error!("cannot spawn a child from {}: {}", scene_path, err);
+                return;
+            }
+        };
+        let Some(mut child) = scene.try_instantiate_as::<Node2D>() else {
+            godot_error!(
+                "cannot spawn a child from {}: root is not a Node2D",
+                scene_path
+            );
+            return;
+        };
+        child.add_to_group(self.config.character_group.as_str().into());
+        self.spawn_sibling(child.clone());
+        child.set_global_position(at);
+        let personality = self
+            .config
+            .personality_for(self.characters.len(), || child.get_name().hash());
+        let character = Character {
+            stock_offset: self.jitter(),
+            ..Character::new(child, personality, self.config.initial_task)
+        };
+        self.characters.push(character);
+        self.last_birth = Some(self.time.day);
+    }
+
+    fn catastrophe(&mut self) {
+        let frost = self.config.frost_loss.min(self.apples.count());
+        if frost > 0 {
+            self.apply(&Outcome::Apples { delta: -frost });
+            self.announce_catastrophe("frost", frost);
+        }
+        if self.headless {
+            return;
+        }
+        let Some(source) = self.source(Self::DEFAULT_JOB) else {
+            return;
+        };
+        let blight = (0..self.config.blight_loss)
+            .take_while(|_| source.pick().is_some())
+            .count() as i64;
+        if blight > 0 {
+            self.announce_catastrophe("blight", blight);
+        }
+    }
+
+    fn announce_catastrophe(&mut self, kind: &str, magnitude: i64) {
+        self.emit(
+            "catastrophe",
+            &[Variant::from(kind), Variant::from(magnitude)],
+        );
+        self.emit_event("catastrophe", dict! {"kind": kind, "magnitude": magnitude});
+    }
+
+    fn emit_event(&self, kind: &str, payload: Dictionary) {
+        self.emit("game_event", &[Variant::from(kind), Variant::from(payload)]);
+    }
+
+    fn emit(&self, signal: &'static str, args: &[Variant]) {
+        if self.headless {
+            return;
+        }
+        self.pending_signals
+            .borrow_mut()
+            .push((signal, args.to_vec()));
+    }
+
+    fn flush_signals(&mut self) {
+        for (signal, args) in self.pending_signals.take() {
+            self.base_mut().emit_signal(signal.into(), &args);
+        }
+    }
+
+    fn windfall(&mut self) {
+        let (min, max) = self.config.windfall_apples;
+        let count = min + self.rng.below(max - min + 1);
+        let Some(source) = self.source(Self::DEFAULT_JOB) else {
+            return;
+        };
+        let mut channels = vec![];
+        for i in 0..count {
+            let apple = if self.headless {
+                self.stockpile.clone()
+            } else if let Some(apple) = source.pick() {
+                apple
+            } else {
+                break;
+            };
+            channels.push(self.send_apple_after(
+                "windfall",
+                i as f64 * Self::GIFT_STAGGER,
+                1000.0,
+                Self::transfer_channel(1, 0.0),
+                &apple,
+                &self.stockpile,
+            ));
+        }
+        self.background.extend(channels);
+    }
+
+    fn curfew(&self) -> bool {
+        self.config.curfew(self.time.phase)
+    }
+
+    fn recall_travelers(&mut self) {
+        let delivered = self.abort_travelers();
+        if delivered > 0 {
+            let outcome = Outcome::Apples { delta: delivered };
+            self.background.push(OutcomeChannel::immediate(outcome));
+        }
+    }
+
+    fn abort_travelers(&self) -> i64 {
+        self.pending_spawns.borrow_mut().clear();
+        let mut delivered = 0;
+        for id in self.travelers.take() {
+            match Gd::<Traveler>::try_from_instance_id(id) {
+                Ok(mut traveler) if !traveler.is_queued_for_deletion() => {
+                    delivered += traveler.bind_mut().abort();
+                }
+                _ => (),
+            }
+        }
+        delivered
+    }
+
+    fn spawn_sibling(&self, sib: Gd<impl Inherits<Node>>) {
+        self.base().get_parent().unwrap().add_child(sib.upcast())
+    }
+
+    fn send_apple(
+        &self,
+        kind: &str,
+        speed: f32,
+        ch: OutcomeChannel,
+        from: &Node2D,
+        to: &Gd<Node2D>,
+    ) -> OutcomeChannel {
+        self.send_apple_after(kind, 0.0, speed, ch, from, to)
+    }
+
+    fn send_apple_after(
+        &self,
+        kind: &str,
+        delay: f64,
+        speed: f32,
+        ch: OutcomeChannel,
+        from: &Node2D,
+        to: &Gd<Node2D>,
+    ) -> OutcomeChannel {
+        if self.curfew() {
+            ch.cancel();
+            return ch;
+        }
+        if self.headless {
+            ch.fire();
+            return ch;
+        }
+        let duration = self.config.trip_durations.get(kind).copied();
+        let speed = match duration.or(self.config.travel_duration) {
+            Some(duration) => {
+                global_position(from).distance_to(global_position(to)) / duration as f32
+            }
+            None => speed,
+        };
+        let mut traveler = Traveler::new(speed, ch.clone(), from, to);
+        traveler.bind_mut().tolerance = self.config.arrival_tolerance;
+        traveler.bind_mut().tag = kind.into();
+        if self.config.track_targets {
+            traveler.bind_mut().follow = Some(to.clone());
+        }
+        self.launch(traveler, delay);
+        self.emit("apple_launched", &[Variant::from(kind)]);
+        self.emit_event("apple_launched", dict! {"kind": kind});
+        ch
+    }
+
+    fn launch(&self, mut traveler: Gd<Traveler>, delay: f64) {
+        let brightness = self
+            .config
+            .traveler_brightness
+            .map(|levels| levels[self.time.phase.index()]);
+        traveler
+            .bind_mut()
+            .load_child("res://apple.tscn", brightness);
+        traveler.bind_mut().hold(delay);
+        self.latest_traveler.set(Some(traveler.instance_id()));
+        self.prune_travelers();
+        self.travelers.borrow_mut().push(traveler.instance_id());
+        self.pending_spawns.borrow_mut().push_back(traveler);
+    }
+
+    fn prune_travelers(&self) {
+        self.travelers.borrow_mut().retain(|id| {
+            Gd::<Traveler>::try_from_instance_id(*id).is_ok_and(|t| !t.is_queued_for_deletion())
+        });
+    }
+
+    fn transfer_channel(delta: i64, withdraw_after: f64) -> OutcomeChannel {
+        match delta.cmp(&0) {
+            Ordering::Less if withdraw_after > 0.0 => {
+                let events = vec![Outcome::StatusQuo, Outcome::Apples { delta }];
+                OutcomeChannel::new(events, 0).after(withdraw_after)
+            }
+            Ordering::Less => {
+                OutcomeChannel::new(vec![Outcome::Apples { delta }, Outcome::StatusQuo], 1)
+            }
+            Ordering::Equal => OutcomeChannel::delayed_noop(),
+            Ordering::Greater => OutcomeChannel::delayed(Outcome::Apples { delta }),
+        }
+    }
+
+    fn transfer_apple(
+        &self,
+        kind: &str,
+        speed: f32,
+        delta: i64,
+        withdraw_after: f64,
+        from: &Node2D,
+        to: &Gd<Node2D>,
+    ) -> OutcomeChannel {
+        let channel = Self::transfer_channel(delta, withdraw_after);
+        self.send_apple(kind, speed, channel, from, to)
+    }
+
+    fn live_source<'a>(
+        sources: &'a HashMap<String, Box<dyn AppleSource>>,
+        job: &str,
+    ) -> Result<&'a dyn AppleSource, &'a str> {
+        let (job, source) = sources
+            .get_key_value(job)
+            .or_else(|| sources.get_key_value(Self::DEFAULT_JOB))
+            .unwrap();
+        if source.valid() {
+            Ok(source.as_ref())
+        } else {
+            Err(job)
+        }
+    }
+
+    fn source(&self, job: &str) -> Option<&dyn AppleSource> {
+        let job = match Self::live_source(&self.sources, job) {
+            Ok(source) => return Some(source),
+            Err(job) => job,
+        };
+        if self.lost_sources.borrow_mut().insert(job.to_string()) {
+            godot_warn!("the apple source for {:?} was freed; work is skipped until a new one is registered", job);
+        }
+        None
+    }
+
+    fn pick_apple(&self, character: &Character) -> Option<OutcomeChannel> {
+        let Some(source) = self.source(&character.job) else {
+            character.harvest.set(0.0);
+            return None;
+        };
+        if self.headless {
+            character.failed_work.set(0);
+            character.harvest.set(source.maturity());
+            return Some(OutcomeChannel::immediate_noop());
+        }
+        let Some(spawn) = source.pick() else {
+            godot_warn!("{} has no apples left to pick", source.node().get_path());
+            character.failed_work.set(character.failed_work.get() + 1);
+            character.harvest.set(0.0);
+            return None;
+        };
+        character.failed_work.set(0);
+        character.harvest.set(source.maturity());
+        Some(self.transfer_apple("pick", 400.0, 0, 0.0, &spawn, &character.graphics))
+    }
+
+    fn eat_apple(&self, character: &Character, serving: i64) -> OutcomeChannel {
+        self.transfer_apple(
+            "eat",
+            1000.0,
+            -serving,
+            self.config.eat_duration,
+            &self.stockpile,
+            &character.graphics,
+        )
+    }
+
+    fn store_apple(&self, character: &Character, amount: i64) -> OutcomeChannel {
+        if let (Some(hoard), Personality::Greedy) = (&self.hoard, &character.personality) {
+            let channel = OutcomeChannel::delayed(Outcome::Hoard { delta: amount });
+            return self.send_apple("hoard", 1000.0, channel, &character.graphics, hoard);
+        }
+        self.transfer_apple(
+            "store",
+            1000.0,
+            amount,
+            0.0,
+            &character.graphics,
+            &self.stockpile,
+        )
+    }
+
+    fn share_target(&self) -> Option<&Character> {
+        let pile = global_position(&self.stockpile);
+        self.characters
+            .iter()
+            .filter(|c| c.hunger.get() > 0.0)
+            .filter(|c| global_position(&c.graphics).distance_to(pile) <= self.config.share_radius)
+            .max_by(|a, b| a.hunger.get().total_cmp(&b.hunger.get()))
+    }
+
+    fn share_apple(&mut self) -> bool {
+        if self.curfew() {
+            return false;
+        }
+        let Some(peer) = self.share_target() else {
+            return false;
+        };
+        peer.eat();
+        let channel = OutcomeChannel::delayed_noop();
+        let channel = self.send_apple("share", 1000.0, channel, &self.stockpile, &peer.graphics);
+        self.background.push(channel);
+        true
+    }
+
+    fn jitter(&self) -> i64 {
+        match self.config.decision_jitter {
+            0 => 0,
+            range => self.rng.below(2 * range as usize + 1) as i64 - range,
+        }
+    }
+
+    fn view(&self) -> WorldView {
+        self.view_at(&self.time)
+    }
+
+    fn view_at<'a>(&'a self, time: &'a GameTime) -> WorldView<'a> {
+        self.config.view(time, self.apples.count())
+    }
+
+    fn character_actions(&mut self) -> Item {
+        let mut traces = vec![];
+        let mut speech = vec![];
+        let deliberate = self
+            .decision_windows
+            .is_multiple_of(self.config.decision_interval);
+        self.decision_windows += 1;
+        for (i, c) in self.characters.iter().enumerate() {
+            let (wanted, reason) = if self.strikes(c) {
+                (Task::Sleep, "on strike → sleep")
+            } else if deliberate {
+                c.decide(self.view(), &[])
+            } else {
+                (c.task.get(), "deliberating → repeat last task")
+            };
+            let (task, reason) = match c.command.take() {
+                Some(task) => (task, "commanded"),
+                None => (wanted, reason),
+            };
+            c.forced.set(task != wanted);
+            c.task.set(task);
+            c.remember(task, self.config.task_history);
+            self.show_icon(c, task);
+            if let Some(line) = self.speak(c, task) {
+                speech.push((i, line));
+            }
+            if self.config.trace_decisions {
+                traces.push(DecisionTrace {
+                    character: i,
+                    phase: self.time.phase,
+                    chosen: task,
+                    reason,
+                });
+            }
+        }
+        self.record_traces(traces);
+        self.break_deadlock();
+        for (i, line) in speech {
+            self.emit(
+                "character_spoke",
+                &[Variant::from(i as i64), Variant::from(line.clone())],
+            );
+            self.emit_event("character_spoke", dict! {"index": i as i64, "line": line});
+        }
+        let mut order: Vec<usize> = (0..self.characters.len()).collect();
+        if self.config.shuffle_actions {
+            self.rng.shuffle(&mut order);
+        }
+        let eaters = order
+            .iter()
+            .copied()
+            .filter(|i| matches!(self.characters[*i].task.get(), Task::Eat | Task::Steal));
+        let servings = self.allocate_meals(eaters);
+        let mut retried = vec![];
+        let actions: Vec<_> = order
+            .iter()
+            .map(|i| {
+                let c = &self.characters[*i];
+                let mut excluded = vec![];
+                loop {
+                    let task = c.task.get();
+                    let dispatched = match task {
+                        Task::Eat | Task::Steal if servings[*i] == 0 => {
+                            Some(OutcomeChannel::immediate_noop())
+                        }
+                        task => self.fulfill(c, task, servings[*i]),
+                    };
+                    match dispatched {
+                        Some(channel) => break channel,
+                        None if excluded.len() < self.config.dispatch_retries => {
+                            excluded.push(task);
+                            let (task, reason) = c.decide(self.view(), &excluded);
+                            c.task.set(task);
+                            retried.push((*i, task, reason));
+                        }
+                        None => {
+                            c.task.set(Task::Sleep);
+                            retried.push((*i, Task::Sleep, "retries exhausted → sleep"));
+                            break OutcomeChannel::immediate_noop();
+                        }
+                    }
+                }
+            })
+            .collect();
+        let mut traces = vec![];
+        for (i, task, reason) in retried {
+            if let Some(last) = self.characters[i].history.borrow_mut().back_mut() {
+                *last = task;
+            }
+            if self.config.trace_decisions {
+                traces.push(DecisionTrace {
+                    character: i,
+                    phase: self.time.phase,
+                    chosen: task,
+                    reason,
+                });
+            }
+        }
+        self.record_traces(traces);
+        self.check_idle();
+        Item::Play(OutcomeMux::from(actions))
+    }
+
+    fn stalled<G>(characters: &[Character<G>], apples: i64) -> bool {
+        apples <= 0 && characters.iter().all(|c| c.task.get() != Task::Work)
+    }
+
+    fn break_deadlock(&mut self) {
+        if !Self::stalled(&self.characters, self.apples.count()) {
+            self.stalled_windows = 0;
+            return;
+        }
+        self.stalled_windows += 1;
+        if !self.config.break_deadlocks || self.stalled_windows < self.time.order.len() {
+            return;
+        }
+        self.stalled_windows = 0;
+        let Some(c) = self.characters.first() else {
+            return;
+        };
+        c.force(Task::Work);
+        if self.config.trace_decisions {
+            self.record_traces([DecisionTrace {
+                character: 0,
+                phase: self.time.phase,
+                chosen: Task::Work,
+                reason: "deadlock: nobody working and no apples → work",
+            }]);
+        }
+    }
+
+    fn record_traces(&mut self, traces: impl IntoIterator<Item = DecisionTrace>) {
+        self.decision_traces.extend(traces);
+        if let Some(limit) = self.config.trace_limit {
+            let excess = self.decision_traces.len().saturating_sub(limit);
+            self.decision_traces.drain(..excess);
+        }
+    }
+
+    fn speak(&self, character: &Character, task: Task) -> Option<String> {
+        let lines: Vec<&String> = self
+            .config
+            .speech_lines
+            .iter()
+            .filter(|(p, t, _)| *p == character.personality && *t == task)
+            .map(|(_, _, line)| line)
+            .collect();
+        if lines.is_empty() || !self.rng.chance(self.config.speech_rate) {
+            return None;
+        }
+        Some(lines[self.rng.below(lines.len())].clone())
+    }
+
+    fn show_icon(&self, character: &Character, task: Task) {
+        let Some(path) = self.config.task_icon(task) else {
+            return;
+        };
+        if self.headless {
+            return;
+        }
+        let scene = match try_load::<PackedScene>(path) {
+            Ok(scene) => scene,
+            Err(err) => {
+                godot_error!("cannot show icon {}: {}", path, err);
+                return;
+            }
+        };
+        let Some(mut icon) = scene.try_instantiate_as::<Node2D>() else {
+            godot_error!("cannot show icon {}: root is not a Node2D", path);
+            return;
+        };
+        icon.set_position(Self::ICON_OFFSET);
+        character.graphics.clone().add_child(icon.clone().upcast());
+        let timer = self
+            .base()
+            .get_tree()
+            .and_then(|mut tree| tree.create_timer(self.config.icon_duration));
+        if let Some(mut timer) = timer {
+            timer.connect(
+                "timeout".into(),
+                Callable::from_object_method(&icon, "queue_free"),
+            );
+        }
+    }
+
+    fn strikes(&self, character: &Character) -> bool {
+        self.config.strikes(character, &self.rng)
+    }
+
+    fn allocate_meals(&self, candidates: impl Iterator<Item = usize>) -> Vec<i64> {
+        let mut eaters: Vec<usize> = candidates.collect();
+        if self.config.feed_hungriest_first {
+            let hunger = |i: &usize| self.characters[*i].hunger.get();
+            eaters.sort_by(|a, b| hunger(b).total_cmp(&hunger(a)));
+        }
+        if let Some(starving) = self.config.starving_hunger {
+            eaters.sort_by_key(|i| self.characters[*i].hunger.get() < starving);
+        }
+        let mut remaining = self.apples.count().max(0);
+        let mut servings = vec![0; self.characters.len()];
+        for i in eaters {
+            if remaining == 0 {
+                break;
+            }
+            let appetite = self.config.appetite(self.characters[i].personality);
+            servings[i] = appetite.min(remaining);
+            remaining -= servings[i];
+        }
+        servings
+    }
+
+    fn check_idle(&mut self) {
+        let idle = self.background.is_empty()
+            && self.characters.iter().all(|c| c.task.get() == Task::Sleep);
+        if idle && !self.idle {
+            self.emit("village_idle", &[]);
+            self.emit_event("village_idle", Dictionary::new());
+        }
+        self.idle = idle;
+    }
+
+    fn character_cleanup(&self) -> Item {
+        let mut cleanups = vec![];
+        let mut workers = 0;
+        for c in self.characters.iter() {
+            let task = c.task.get();
+            cleanups.push(self.finish(c, task, workers));
+            c.settle();
+            if self.time.phase == Phase::Night {
+                c.account_night(task == Task::Sleep);
+            }
+            if task == Task::Work {
+                c.tire(self.config.sleep_debt_factor);
+                workers += 1;
+            }
+        }
+        Item::Play(OutcomeMux::from(cleanups))
+    }
+
+    fn schedule_item(&mut self) -> Item {
+        match self.time.sub {
+            sub if sub == self.config.decision_sub => self.character_actions(),
+            sub if sub == self.config.cleanup_sub => self.character_cleanup(),
+            sub if self.config.meal_times.contains(&(self.time.phase, sub)) => self.communal_meal(),
+            _ => Item::wait(Self::SUB_PHASE_WAIT),
+        }
+    }
+
+    fn waits_at(&self, time: &GameTime) -> bool {
+        time.sub != self.config.decision_sub
+            && time.sub != self.config.cleanup_sub
+            && !self.config.meal_times.contains(&(time.phase, time.sub))
+    }
+
+    fn enqueue(&mut self, item: Item) {
+        let waiting = matches!(item, Item::Wait { .. });
+        Item::push(&mut self.queue, item);
+        let mut time = self.time.clone();
+        while waiting && time.sub.next() != SubPhase::Commence {
+            time.next();
+            if !self.waits_at(&time) {
+                break;
+            }
+            Item::push(&mut self.queue, Item::wait(Self::SUB_PHASE_WAIT));
+        }
+    }
+
+    fn communal_meal(&self) -> Item {
+        if self.curfew() {
+            return Item::Play(OutcomeMux::from([]));
+        }
+        let servings = self.allocate_meals(0..self.characters.len());
+        let meals: Vec<_> = self
+            .characters
+            .iter()
+            .zip(servings)
+            .filter(|(_, serving)| *serving > 0)
+            .map(|(c, serving)| {
+                c.eat();
+                self.eat_apple(c, serving)
+            })
+            .collect();
+        Item::Play(OutcomeMux::from(meals))
+    }
+
+    fn flush_spawns(&self) {
+        let budget = self.config.spawns_per_frame.unwrap_or(usize::MAX);
+        for _ in 0..budget {
+            let Some(traveler) = self.pending_spawns.borrow_mut().pop_front() else {
+                return;
+            };
+            self.spawn_sibling(traveler);
+        }
+    }
+
+    fn tick_background(&mut self, delta: f64) -> Vec<Outcome> {
+        if self.background.is_empty() {
+            return vec![];
+        }
+        let channels = std::mem::take(&mut self.background);
+        let (outcomes, rest) = OutcomeMux::from(channels).tick(delta);
+        if let Some(rest) = rest {
+            self.background = rest.channels;
+        }
+        outcomes
+    }
+
+    fn witness_theft(&self, thief: &Character) {
+        let scene = thief.graphics.get_global_position();
+        for c in &self.characters {
+            let close =
+                c.graphics.get_global_position().distance_to(scene) <= self.config.alert_radius;
+            if close && matches!(c.personality, Personality::Cooperative) {
+                c.alert.set(self.config.alert_duration);
+            }
+        }
+    }
+
+    fn character(&self, index: i64) -> Option<&Character> {
+        usize::try_from(index)
+            .ok()
+            .and_then(|index| self.characters.get(index))
+    }
+
+    fn resolve(&mut self, mut item: Item) {
+        loop {
+            let (outcomes, next) = item.tick(f64::INFINITY);
+            self.apply_all(outcomes);
+            match next {
+                Some(next) => item = next,
+                None => return,
+            }
+        }
+    }
+
+    fn settle_background(&mut self) {
+        while !self.background.is_empty() {
+            let outcomes = OutcomeMux::from(std::mem::take(&mut self.background)).settle();
+            self.apply_all(outcomes);
+        }
+    }
+
+    fn fast_forward(&mut self, done: impl Fn(&GameTime) -> bool) {
+        self.headless = true;
+        while !done(&self.time) {
+            let item = self.schedule_item();
+            self.resolve(item);
+            self.advance_time();
+            self.settle_background();
+        }
+        self.headless = false;
+        self.flush_stockpile();
+        self.show_time();
+        self.flush_signals();
+    }
+
+    fn show_time(&mut self) {
+        let shown = Some((self.time.phase, self.time.day));
+        if self.shown_time == shown {
+            return;
+        }
+        self.shown_time = shown;
+        self.time_indicator.call(
+            "set_time".into(),
+            &[
+                Variant::from(format!("{:?}", self.time.phase)),
+                Variant::from(format!("{}", self.time.day)),
+            ],
+        );
+        self.tint_characters();
+    }
+
+    fn tint_characters(&self) {
+        let Some(tints) = self.config.phase_tints else {
+            return;
+        };
+        let tint = tints[self.time.phase.index()];
+        for c in &self.characters {
+            c.graphics.clone().set_modulate(tint);
+        }
+    }
+}
+
+#[godot_api]
+impl Controller {
+    #[constant]
+    const PHASE_PREDAWN: i64 = 0;
+    #[constant]
+    const PHASE_MORNING: i64 = 1;
+    #[constant]
+    const PHASE_MIDDAY: i64 = 2;
+    #[constant]
+    const PHASE_EVENING: i64 = 3;
+    #[constant]
+    const PHASE_NIGHT: i64 = 4;
+    #[constant]
+    const SUB_COMMENCE: i64 = 0;
+    #[constant]
+    const SUB_PROGRESS: i64 = 1;
+    #[constant]
+    const SUB_COMPLETE: i64 = 2;
+    #[constant]
+    const SUB_TEMPO: i64 = 3;
+
+    #[signal]
+    fn village_idle();
+
+    #[signal]
+    fn apple_launched(kind: GString);
+
+    #[signal]
+    fn character_spoke(index: i64, line: GString);
+
+    #[signal]
+    fn catastrophe(kind: GString, magnitude: i64);
+
+    #[signal]
+    fn game_event(kind: GString, payload: Dictionary);
+
+    #[signal]
+    fn low_stock(current: i64);
+
+    #[signal]
+    fn stock_recovered(current: i64);
+
+    #[func]
+    fn decision_traces(&self) -> Array<Dictionary> {
+        self.decision_traces
+            .iter()
+            .map(DecisionTrace::to_dictionary)
+            .collect()
+    }
+
+    #[func]
+    fn character_personality(&self, index: i64) -> GString {
+        self.character(index)
+            .map(|c| GString::from(c.personality.name()))
+            .unwrap_or_default()
+    }
+
+    #[func]
+    fn characters_snapshot(&self) -> Array<Dictionary> {
+        self.characters
+            .iter()
+            .enumerate()
+            .map(|(i, c)| c.snapshot(i))
+            .collect()
+    }
+
+    #[func]
+    fn character_task_history(&self, index: i64) -> Array<GString> {
+        self.character(index)
+            .map(|c| {
+                c.history
+                    .borrow()
+                    .iter()
+                    .map(|task| GString::from(format!("{:?}", task)))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    #[func]
+    fn character_satisfaction(&self, index: i64) -> f64 {
+        self.character(index).map_or(0.0, |c| c.satisfaction.get())
+    }
+
+    #[func]
+    fn time_of_day(&self) -> f64 {
+        self.time.time_of_day()
+    }
+
+    #[func]
+    fn diagnostics(&self) -> Dictionary {
+        self.prune_travelers();
+        let mut personalities = Dictionary::new();
+        for c in &self.characters {
+            let name = c.personality.name();
+            let count = personalities
+                .get(name)
+                .and_then(|n| n.try_to::<i64>().ok())
+                .unwrap_or(0);
+            personalities.set(name, count + 1);
+        }
+        let mut dict = Dictionary::new();
+        dict.set("day", self.time.day);
+        dict.set("phase", format!("{:?}", self.time.phase));
+        dict.set("sub", format!("{:?}", self.time.sub));
+        dict.set("season", self.time.season().name());
+        dict.set("apples", self.apples.count());
+        dict.set("hoarded", self.hoarded);
+        dict.set("queue_depth", self.queue.len() as i64);
+        dict.set("travelers", self.travelers.borrow().len() as i64);
+        let pending: usize = self.background.iter().map(OutcomeChannel::pending).sum();
+        dict.set("background_pending", pending as i64);
+        dict.set("personalities", personalities);
+        dict
+    }
+
+    #[func]
+    fn travelers_with_tag(&self, tag: StringName) -> i64 {
+        self.prune_travelers();
+        self.travelers
+            .borrow()
+            .iter()
+            .filter_map(|id| Gd::<Traveler>::try_from_instance_id(*id).ok())
+            .filter(|t| t.bind().tag == tag)
+            .count() as i64
+    }
+
+    #[func]
+    fn apple_price(&self) -> f64 {
+        self.config.apple_price(self.apples.count())
+    }
+
+    #[func]
+    fn phase_index(&self) -> i64 {
+        self.time.phase.index() as i64
+    }
+
+    #[func]
+    fn sub_index(&self) -> i64 {
+        self.time.sub.index() as i64
+    }
+
+    #[func]
+    fn current_season(&self) -> GString {
+        GString::from(self.time.season().name())
+    }
+
+    #[func]
+    fn forecast_demand(&self) -> i64 {
+        let upcoming = self.time.next_at(self.config.decision_sub);
+        self.characters
+            .iter()
+            .filter(|c| {
+                let task = c
+                    .command
+                    .get()
+                    .unwrap_or_else(|| c.decide(self.view_at(&upcoming), &[]).0);
+                task == Task::Eat
+            })
+            .count() as i64
+    }
+
+    #[func]
+    fn simulate_character(&self, index: i64, days: i64) -> Array<GString> {
+        let Some(c) = self.character(index) else {
+            godot_warn!("simulate_character: no character at index {}", index);
+            return Array::new();
+        };
+        let until = self.time.day + days.max(0);
+        let mut time = self.time.next_at(self.config.decision_sub);
+        let mut tasks = Array::new();
+        while time.day < until {
+            let task = c.decide(self.view_at(&time), &[]).0;
+            tasks.push(GString::from(format!("{:?}", task)));
+            time = time.next_at(self.config.decision_sub);
+        }
+        tasks
+    }
+
+    #[func]
+    fn report_theft(&self, thief: i64) {
+        match self.character(thief) {
+            Some(c) => self.witness_theft(c),
+            None => godot_warn!("report_theft: no character at index {}", thief),
+        }
+    }
+
+    #[func]
+    fn set_outcome_transform(&mut self, transform: Callable) {
+        self.outcome_transform = Some(transform).filter(Callable::is_valid);
+    }
+
+    #[func]
+    fn apply_config(&mut self, cyst: Gd<Cyst>) {
+        let cyst = cyst.bind();
+        let mut config = cyst.config();
+        let Some((time, stock, tree)) = cyst.parts() else {
+            godot_error!(
+                "apply_config: Cyst is missing its time indicator, stockpile or apple tree"
+            );
+            return;
+        };
+        let errors = Cyst::wiring_errors(&time, &stock, &tree, &config.stockpile_property);
+        for error in &errors {
+            godot_error!("apply_config: Cyst is miswired: {}", error);
+        }
+        if !errors.is_empty() {
+            return;
+        }
+        let startup_only = [
+            ("phase_order", config.phase_order != self.config.phase_order),
+            (
+                "character_group",
+                config.character_group != self.config.character_group,
+            ),
+            (
+                "max_characters",
+                config.max_characters != self.config.max_characters,
+            ),
+            ("seed", config.seed != self.config.seed),
+            (
+                "initial_task",
+                config.initial_task != self.config.initial_task,
+            ),
+            (
+                "personality weights",
+                config.personality_weights != self.config.personality_weights,
+            ),
+            (
+                "personality_by_index",
+                config.personality_by_index != self.config.personality_by_index,
+            ),
+        ];
+        for (name, _) in startup_only.iter().filter(|(_, changed)| *changed) {
+            godot_warn!(
+                "apply_config: {} is only read at startup, keeping the current value",
+                name
+            );
+        }
+        config.phase_order = self.config.phase_order.clone();
+        config.character_group = self.config.character_group.clone();
+        config.max_characters = self.config.max_characters;
+        config.seed = self.config.seed;
+        config.initial_task = self.config.initial_task;
+        config.personality_weights = self.config.personality_weights;
+        config.personality_by_index = self.config.personality_by_index;
+        self.recall_travelers();
+        self.hoard = cyst.greedy_hoard.clone();
+        self.display.stack.restack(self.config.stack_layout, 0);
+        self.display.stack = StackedApples::new(stock.clone());
+        self.display.sink = Box::new(PropertySink {
+            node: stock.clone(),
+            apples_property: config.stockpile_property.as_str().into(),
+        });
+        self.stockpile = stock;
+        self.time_indicator = time;
+        self.config = config;
+        self.set_apple_tree(tree);
+        self.shown_time = None;
+        self.show_time();
+        self.sync_stockpile();
+        self.flush_signals();
+    }
+
+    #[func]
+    fn set_apple_tree(&mut self, tree: Gd<Node>) {
+        self.register_source(Self::DEFAULT_JOB.into(), tree);
+    }
+
+    #[func]
+    fn register_source(&mut self, job: GString, source: Gd<Node>) {
+        let path = source.get_path();
+        match apple_source(source) {
+            Some(source) => {
+                self.lost_sources.borrow_mut().remove(&job.to_string());
+                self.sources.insert(job.to_string(), source);
+            }
+            None => godot_warn!("register_source: {} has no pick() method", path),
+        }
+    }
+
+    #[func]
+    fn assign_job(&mut self, index: i64, job: GString) {
+        let job = job.to_string();
+        if !self.sources.contains_key(&job) {
+            godot_warn!("assign_job: no source registered for {:?}", job);
+            return;
+        }
+        match usize::try_from(index)
+            .ok()
+            .and_then(|i| self.characters.get_mut(i))
+        {
+            Some(c) => c.job = job,
+            None => godot_warn!("assign_job: no character at index {}", index),
+        }
+    }
+
+    #[func]
+    fn command_task(&mut self, index: i64, task: GString) {
+        let Some(character) = self.character(index) else {
+            godot_warn!("command_task: no character at index {}", index);
+            return;
+        };
+        match Task::parse(&task.to_string()) {
+            Some(task) => character.command.set(Some(task)),
+            None => godot_warn!("command_task: unknown task {:?}", task.to_string()),
+        }
+    }
+
+    #[func]
+    fn latest_traveler(&self) -> Option<Gd<Traveler>> {
+        let traveler = Gd::<Traveler>::try_from_instance_id(self.latest_traveler.get()?).ok()?;
+        if traveler.is_queued_for_deletion() {
+            self.latest_traveler.set(None);
+            return None;
+        }
+        Some(traveler)
+    }
+
+    #[func]
+    fn gift_apples(&mut self, count: i64, from: Gd<Node2D>) {
+        for i in 0..count.max(0) {
+            let channel = self.send_apple_after(
+                "gift",
+                i as f64 * Self::GIFT_STAGGER,
+                1000.0,
+                Self::transfer_channel(1, 0.0),
+                &from,
+                &self.stockpile,
+            );
+            self.background.push(channel);
+        }
+        self.flush_signals();
+    }
+
+    #[func]
+    fn apply_batch(&mut self, outcomes: Array<Dictionary>) {
+        let parsed = outcomes
+            .iter_shared()
+            .filter_map(|dict| {
+                let outcome = Outcome::from_dictionary(&dict);
+                if outcome.is_none() {
+                    godot_warn!("apply_batch: skipping malformed outcome {}", dict);
+                }
+                outcome
+            })
+            .collect();
+        self.apply_all(parsed);
+        self.flush_stockpile();
+        self.flush_signals();
+    }
+
+    #[func]
+    fn export_timeseries(&self) -> GString {
+        let mut csv = String::from("day,apples\n");
+        for (day, apples) in &self.daily_totals {
+            csv.push_str(&format!("{},{}\n", day, apples));
+        }
+        GString::from(csv)
+    }
+
+    #[func]
+    fn debug_dump(&self) -> GString {
+        self.prune_travelers();
+        let mut dump = format!(
+            "time day={} phase={:?} sub={:?} season={}\n",
+            self.time.day,
+            self.time.phase,
+            self.time.sub,
+            self.time.season().name()
+        );
+        dump.push_str(&format!(
+            "apples stock={} hoarded={}\n",
+            self.apples.count(),
+            self.hoarded
+        ));
+        for (i, c) in self.characters.iter().enumerate() {
+            dump.push_str(&format!(
+                "character {} personality={} task={:?}\n",
+                i,
+                c.personality.name(),
+                c.task.get()
+            ));
+        }
+        for (i, item) in self.queue.iter().enumerate() {
+            match item {
+                Item::Wait {
+                    seconds,
+                    sub_phases,
+                } => dump.push_str(&format!(
+                    "queue {} wait seconds={} sub_phases={}\n",
+                    i, seconds, sub_phases
+                )),
+                Item::Play(mux) => {
+                    let pending: usize = mux.channels.iter().map(OutcomeChannel::pending).sum();
+                    dump.push_str(&format!(
+                        "queue {} play channels={} pending={}\n",
+                        i,
+                        mux.channels.len(),
+                        pending
+                    ))
+                }
+            }
+        }
+        dump.push_str(&format!(
+            "travelers live={}\n",
+            self.travelers.borrow().len()
+        ));
+        GString::from(dump)
+    }
+
+    #[func]
+    fn save_state(&self) -> Dictionary {
+        let mut state = Dictionary::new();
+        state.set("day", self.time.day);
+        state.set("phase", format!("{:?}", self.time.phase));
+        state.set("sub", format!("{:?}", self.time.sub));
+        let stored: Vec<i64> = self.apples.stored_on.iter().copied().collect();
+        state.set("apples", PackedInt64Array::from(&stored[..]));
+        state.set("rng", self.rng.state() as i64);
+        let queue: Array<Dictionary> = self.queue.iter().map(Item::to_dictionary).collect();
+        state.set("queue", queue);
+        let background: Array<Dictionary> = self
+            .background
+            .iter()
+            .map(OutcomeChannel::to_dictionary)
+            .collect();
+        state.set("background", background);
+        self.prune_travelers();
+        let travelers: Array<Dictionary> = self
+            .travelers
+            .borrow()
+            .iter()
+            .filter_map(|id| Gd::<Traveler>::try_from_instance_id(*id).ok())
+            .map(|traveler| traveler.bind().to_dictionary())
+            .collect();
+        state.set("travelers", travelers);
+        state
+    }
+
+    #[func]
+    fn load_state(&mut self, state: Dictionary) {
+        let Some(saved) = SavedState::parse(&state) else {
+            godot_warn!("load_state: ignoring malformed save {}", state);
+            return;
+        };
+        if self.time.position(saved.phase).is_none() {
+            godot_warn!("load_state: {:?} is not in the phase order", saved.phase);
+            return;
+        }
+        self.abort_travelers();
+        let channels: Vec<OutcomeChannel> = saved
+            .queue
+            .iter()
+            .flat_map(|item| match item {
+                Item::Play(mux) => &mux.channels[..],
+                Item::Wait { .. } => &[][..],
+            })
+            .chain(&saved.background)
+            .cloned()
+            .collect();
+        self.queue = saved.queue.into();
+        self.background = saved.background;
+        self.time.day = saved.day;
+        self.time.phase = saved.phase;
+        self.time.sub = saved.sub;
+        match saved.travelers {
+            Some(travelers) => {
+                for traveler in &travelers {
+                    match channels.iter().find(|c| c.seq == traveler.channel) {
+                        Some(channel) => self
+                            .launch(Traveler::restore(traveler, channel.clone()), traveler.delay),
+                        None => godot_warn!(
+                            "load_state: dropping a traveler for unknown channel {}",
+                            traveler.channel
+                        ),
+                    }
+                }
+            }
+            None => channels.iter().for_each(OutcomeChannel::land),
+        }
+        self.apples = Stockpile::from_days(saved.apples);
+        self.rng.set_state(saved.rng);
+        self.sync_stockpile();
+        self.show_time();
+        self.flush_signals();
+    }
+
+    #[func]
+    fn prewarm(&mut self, days: i64) {
+        if !self.queue.is_empty() {
+            godot_warn!("prewarm skipped: the scheduler is already running");
+            return;
+        }
+        let until = self.time.day + days.max(0);
+        self.fast_forward(|time| time.day >= until);
+    }
+
+    #[func]
+    fn skip_to_phase(&mut self, phase: GString) {
+        let target = match self.time.parse_phase(&phase.to_string()) {
+            Ok(target) => target,
+            Err(err) => {
+                godot_warn!("skip_to_phase: {}", err);
+                return;
+            }
+        };
+        self.headless = true;
+        while let Some(item) = self.queue.pop_front() {
+            let (outcomes, sub_phases) = item.settle();
+            self.apply_all(outcomes);
+            (0..sub_phases).for_each(|_| self.advance_time());
+        }
+        self.settle_background();
+        self.abort_travelers();
+        self.fast_forward(|time| time.phase == target);
+    }
+}
+
+#[godot_api]
+impl INode for Controller {
+    fn process(&mut self, delta: f64) {
+        let delta = delta.clamp(self.config.min_frame_delta, self.config.max_frame_delta);
+        let mut outcomes = self.tick_background(delta);
+        let current = self.queue.pop_front();
+        match current {
+            None => {
+                self.apply_all(outcomes);
+                let item = self.schedule_item();
+                self.enqueue(item)
+            }
+            Some(current) => {
+                let sub_phases = current.sub_phases();
+                let (due, next) = current.tick(delta);
+                outcomes.extend(due);
+                self.apply_all(outcomes);
+                match next {
+                    Some(next) => self.queue.push_front(next),
+                    None => (0..sub_phases).for_each(|_| self.advance_time()),
+                }
+                self.show_time();
+            }
+        }
+        self.flush_stockpile();
+        self.flush_spawns();
+        self.flush_signals();
+    }
+
+    fn enter_tree(&mut self) {
+        let mut nodes: Vec<Gd<Node2D>> = self
+            .base()
+            .get_tree()
+            .unwrap()
+            .get_nodes_in_group(self.config.character_group.as_str().into())
+            .iter_shared()
+            .map(|node| node.cast())
+            .collect();
+        nodes.sort_by_cached_key(|node| node.get_path().to_string());
+        if let Some(max) = self.config.max_characters {
+            for skipped in nodes.iter().skip(max) {
+                godot_warn!(
+                    "max_characters reached, not simulating {}",
+                    skipped.get_path()
+                );
+            }
+            nodes.truncate(max);
+        }
+        let config = &self.config;
+        self.characters = nodes
+            .into_iter()
+            .enumerate()
+            .map(|(i, node)| {
+                let personality = config.personality_for(i, || node.get_name().hash());
+                Character {
+                    stock_offset: self.jitter(),
+                    ..Character::new(node, personality, config.initial_task)
+                }
+            })
+            .collect();
+    }
+}
+
+#[godot_api]
+impl INode for Cyst {
+    fn enter_tree(&mut self) {
+        Controller::new(self).map(|controller| self.base_mut().add_child(controller.upcast()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn time_at(phase: Phase, sub: SubPhase) -> GameTime {
+        GameTime {
+            day: 1,
+            phase,
+            sub,
+            order: Rc::from(&GameTime::DEFAULT_ORDER[..]),
+        }
+    }
+
+    #[test]
+    fn skipping_from_predawn_to_midday_takes_two_phases_of_sub_phases() {
+        let mut time = time_at(Phase::Predawn, SubPhase::Commence);
+        let mut steps = 0;
+        while time.phase != Phase::Midday {
+            time.next();
+            steps += 1;
+        }
+        assert_eq!(steps, 2 * SubPhase::COUNT);
+        assert_eq!(time.sub, SubPhase::Commence);
+        assert_eq!(time.day, 1);
+    }
+
+    #[test]
+    fn trailing_waits_merge_and_cover_both_sub_phases() {
+        let mut queue = VecDeque::new();
+        Item::push(&mut queue, Item::wait(0.5));
+        Item::push(&mut queue, Item::wait(0.25));
+        assert_eq!(queue.len(), 1);
+        assert!(matches!(
+            queue[0],
+            Item::Wait { seconds, sub_phases: 2 } if seconds == 0.75
+        ));
+    }
+
+    #[test]
+    fn waits_do_not_merge_across_play_items() {
+        let mut queue = VecDeque::new();
+        Item::push(&mut queue, Item::wait(0.5));
+        let play = OutcomeMux::from([OutcomeChannel::immediate_noop()]);
+        Item::push(&mut queue, Item::Play(play));
+        Item::push(&mut queue, Item::wait(0.5));
+        assert_eq!(queue.len(), 3);
+        assert_eq!(queue[2].sub_phases(), 1);
+    }
+
+    fn apples(delta: i64) -> Outcome {
+        Outcome::Apples { delta }
+    }
+
+    #[test]
+    fn available_outcomes_are_delivered_in_order() {
+        let channel = OutcomeChannel::new(vec![apples(1), apples(2)], 2);
+        let (first, rest) = channel.check(0.0);
+        assert!(matches!(first, Some(Outcome::Apples { delta: 1 })));
+        let (second, rest) = rest.unwrap().check(0.0);
+        assert!(matches!(second, Some(Outcome::Apples { delta: 2 })));
+        let (done, rest) = rest.unwrap().check(0.0);
+        assert!(done.is_none() && rest.is_none());
+    }
+
+    #[test]
+    fn unfired_outcomes_wait_until_fired() {
+        let channel = OutcomeChannel::delayed(apples(1));
+        let (outcome, rest) = channel.check(1.0);
+        assert!(outcome.is_none());
+        let rest = rest.unwrap();
+        rest.fire();
+        let (outcome, _) = rest.check(0.0);
+        assert!(matches!(outcome, Some(Outcome::Apples { delta: 1 })));
+    }
+
+    #[test]
+    fn timers_fire_once_they_run_out() {
+        let channel = OutcomeChannel::delayed(apples(1)).after(1.0);
+        let (outcome, rest) = channel.check(0.5);
+        assert!(outcome.is_none());
+        let (outcome, _) = rest.unwrap().check(0.5);
+        assert!(matches!(outcome, Some(Outcome::Apples { delta: 1 })));
+    }
+
+    #[test]
+    fn availability_lagging_consumption_keeps_waiting() {
+        let channel = OutcomeChannel::new(vec![apples(1), apples(2), apples(3)], 1);
+        channel.consumed.set(2);
+        let (outcome, rest) = channel.check(0.0);
+        assert!(outcome.is_none());
+        let rest = rest.unwrap();
+        rest.fire();
+        let (outcome, rest) = rest.check(0.0);
+        assert!(outcome.is_none());
+        let rest = rest.unwrap();
+        rest.fire();
+        let (outcome, _) = rest.check(0.0);
+        assert!(matches!(outcome, Some(Outcome::Apples { delta: 3 })));
+    }
+
+    #[test]
+    fn cancelled_channels_finish_without_outcomes() {
+        let channel = OutcomeChannel::new(vec![apples(1), apples(2)], 2);
+        channel.cancel();
+        let (outcome, rest) = channel.check(0.0);
+        assert!(outcome.is_none() && rest.is_none());
+    }
+
+    #[test]
+    fn stockpile_takes_and_spoils_the_oldest_apples_first() {
+        let mut stock = Stockpile::default();
+        stock.store(1, 2);
+        stock.store(3, 2);
+        stock.take(1);
+        assert_eq!(Vec::from(stock.stored_on.clone()), vec![1, 3, 3]);
+        assert_eq!(stock.spoil(5, 3), 1);
+        assert_eq!(Vec::from(stock.stored_on.clone()), vec![3, 3]);
+        assert_eq!(stock.count(), 2);
+    }
+
+    #[test]
+    fn restored_rng_state_continues_the_same_sequence() {
+        let original = Rng::seeded(42);
+        original.next_u64();
+        original.next_u64();
+        let restored = Rng::seeded(0);
+        restored.set_state(original.state());
+        for _ in 0..8 {
+            assert_eq!(restored.next_u64(), original.next_u64());
+        }
+    }
+
+    #[test]
+    fn custom_phase_order_rolls_the_day_over_after_its_last_phase() {
+        let mut time = GameTime {
+            day: 1,
+            phase: Phase::Midday,
+            sub: SubPhase::Tempo,
+            order: Rc::from(&[Phase::Morning, Phase::Midday, Phase::Night][..]),
+        };
+        time.next();
+        assert_eq!((time.phase, time.day), (Phase::Night, 1));
+        time = time.next_at(SubPhase::Tempo);
+        time.next();
+        assert_eq!(
+            (time.phase, time.sub, time.day),
+            (Phase::Morning, SubPhase::Commence, 2)
+        );
+    }
+
+    #[test]
+    fn time_of_day_wraps_to_zero_at_the_day_boundary() {
+        let mut time = time_at(Phase::Night, SubPhase::Tempo);
+        assert!((time.time_of_day() - 0.95).abs() < 1e-9);
+        time.next();
+        assert_eq!(time.day, 2);
+        assert_eq!(time.time_of_day(), 0.0);
+    }
+
+    #[test]
+    fn shuffles_are_reproducible_per_seed() {
+        let shuffled = |seed| {
+            let mut order: Vec<usize> = (0..10).collect();
+            Rng::seeded(seed).shuffle(&mut order);
+            order
+        };
+        assert_eq!(shuffled(7), shuffled(7));
+        assert_ne!(shuffled(7), shuffled(8));
+        let mut sorted = shuffled(7);
+        sorted.sort();
+        assert_eq!(sorted, (0..10).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn mux_drains_channels_in_creation_order() {
+        let first = OutcomeChannel::immediate(apples(1));
+        let second = OutcomeChannel::immediate(apples(2));
+        let (outcomes, _) = OutcomeMux::from([second, first]).tick(0.0);
+        let deltas: Vec<i64> = outcomes
+            .iter()
+            .map(|outcome| match outcome {
+                Outcome::Apples { delta } => *delta,
+                _ => 0,
+            })
+            .collect();
+        assert_eq!(deltas, vec![1, 2]);
+    }
+
+    #[test]
+    fn legacy_weights_match_the_original_name_hash_split() {
+        for hash in 0..64 {
+            let greedy = PersonalityWeights::LEGACY.assign(hash) == Personality::Greedy;
+            assert_eq!(greedy, hash % 4 == 1, "hash {}", hash);
+        }
+    }
+
+    #[test]
+    fn timed_withdrawals_wait_for_the_timer_even_after_arrival() {
+        let channel = Controller::transfer_channel(-1, 1.0);
+        channel.fire();
+        let (outcome, rest) = channel.check(0.5);
+        assert!(matches!(outcome, Some(Outcome::StatusQuo)));
+        let (outcome, rest) = rest.unwrap().check(0.25);
+        assert!(outcome.is_none());
+        let (outcome, _) = rest.unwrap().check(0.25);
+        assert!(matches!(outcome, Some(Outcome::Apples { delta: -1 })));
+    }
+
+    #[test]
+    fn timed_withdrawals_wait_for_arrival_after_the_timer() {
+        let channel = Controller::transfer_channel(-1, 1.0);
+        let (outcome, rest) = channel.check(1.0);
+        assert!(matches!(outcome, Some(Outcome::StatusQuo)));
+        let rest = rest.unwrap();
+        let (outcome, rest) = rest.check(1.0);
+        assert!(outcome.is_none());
+        let rest = rest.unwrap();
+        rest.fire();
+        let (outcome, _) = rest.check(0.0);
+        assert!(matches!(outcome, Some(Outcome::Apples { delta: -1 })));
+    }
+
+    fn view(time: &GameTime, apple_stock: i64) -> WorldView<'_> {
+        Config::default().view(time, apple_stock)
+    }
+
+    fn villager(personality: Personality) -> Character<()> {
+        Character::new((), personality, Task::Sleep)
+    }
+
+    #[test]
+    fn lazy_characters_sleep_outside_midday_unless_the_stockpile_is_empty() {
+        let lazy = villager(Personality::Lazy);
+        for phase in GameTime::DEFAULT_ORDER {
+            let time = time_at(phase, SubPhase::Commence);
+            let expected = match phase {
+                Phase::Midday => Task::Eat,
+                _ => Task::Sleep,
+            };
+            assert_eq!(lazy.decide(view(&time, 5), &[]).0, expected, "{:?}", phase);
+            let expected = match phase {
+                Phase::Predawn | Phase::Night => Task::Sleep,
+                _ => Task::Work,
+            };
+            assert_eq!(lazy.decide(view(&time, 0), &[]).0, expected, "{:?}", phase);
+        }
+    }
+
+    #[test]
+    fn failed_work_falls_back_to_sleep() {
+        let worker = villager(Personality::Cooperative);
+        let time = time_at(Phase::Morning, SubPhase::Commence);
+        assert_eq!(worker.decide(view(&time, 5), &[]).0, Task::Work);
+        let (task, reason) = worker.decide(view(&time, 5), &[Task::Work]);
+        assert_eq!(task, Task::Sleep);
+        assert_eq!(reason, "retry: first choice failed → fallback");
+    }
+
+    struct FreedSource;
+
+    impl AppleSource for FreedSource {
+        fn node(&self) -> Gd<Node> {
+            unreachable!("a freed source has no node")
+        }
+
+        fn valid(&self) -> bool {
+            false
+        }
+
+        fn pick(&self) -> Option<Gd<Node2D>> {
+            unreachable!("a freed source is never picked")
+        }
+    }
+
+    #[test]
+    fn freeing_the_tree_makes_pick_a_safe_noop() {
+        let sources: HashMap<String, Box<dyn AppleSource>> = HashMap::from([(
+            String::from(Controller::DEFAULT_JOB),
+            Box::new(FreedSource) as _,
+        )]);
+        assert_eq!(
+            Controller::live_source(&sources, Controller::DEFAULT_JOB).err(),
+            Some(Controller::DEFAULT_JOB)
+        );
+        assert_eq!(
+            Controller::live_source(&sources, "orchard").err(),
+            Some(Controller::DEFAULT_JOB)
+        );
+    }
+
+    #[test]
+    fn greedy_characters_sleep_at_night_with_a_traced_reason() {
+        let greedy = villager(Personality::Greedy);
+        let time = time_at(Phase::Night, SubPhase::Commence);
+        assert_eq!(
+            greedy.decide(view(&time, 5), &[]),
+            (Task::Sleep, "greedy: night → sleep")
+        );
+    }
+
+    #[test]
+    fn nobody_eats_on_a_gather_day() {
+        let config = Config {
+            feast_cycle: Some(3),
+            ..Config::default()
+        };
+        assert!(config.day_kind(3) == DayKind::Feast);
+        assert!(config.day_kind(4) == DayKind::Gather);
+        for personality in [
+            Personality::Cooperative,
+            Personality::Greedy,
+            Personality::Lazy,
+        ] {
+            for phase in GameTime::DEFAULT_ORDER {
+                let time = GameTime {
+                    day: 4,
+                    ..time_at(phase, SubPhase::Commence)
+                };
+                let task = villager(personality).decide(config.view(&time, 5), &[]).0;
+                assert_ne!(task, Task::Eat, "{} at {:?}", personality.name(), phase);
+            }
+        }
+    }
+
+    #[test]
+    fn characters_below_the_strike_threshold_refuse_to_work() {
+        let config = Config {
+            strike_threshold: Some(0.5),
+            strike_chance: 1.0,
+            ..Config::default()
+        };
+        let rng = Rng::seeded(1);
+        let unhappy = villager(Personality::Cooperative);
+        unhappy.satisfaction.set(0.1);
+        assert!(config.strikes(&unhappy, &rng));
+        assert!(!config.strikes(&villager(Personality::Cooperative), &rng));
+    }
+
+    #[test]
+    fn characters_defer_eating_at_or_below_the_reserve() {
+        let config = Config {
+            stockpile_capacity: Some(10),
+            eat_ratio: 0.5,
+            ..Config::default()
+        };
+        let time = time_at(Phase::Midday, SubPhase::Commence);
+        let cooperative = villager(Personality::Cooperative);
+        assert_eq!(cooperative.decide(config.view(&time, 4), &[]).0, Task::Work);
+        assert_eq!(cooperative.decide(config.view(&time, 6), &[]).0, Task::Eat);
+        assert_eq!(cooperative.decide(view(&time, 4), &[]).0, Task::Eat);
+    }
+
+    #[test]
+    fn repeated_failed_work_switches_away_from_work() {
+        let config = Config {
+            work_aversion: 0.5,
+            ..Config::default()
+        };
+        let time = time_at(Phase::Morning, SubPhase::Commence);
+        let worker = villager(Personality::Cooperative);
+        worker.failed_work.set(1);
+        assert_eq!(worker.decide(config.view(&time, 5), &[]).0, Task::Work);
+        worker.failed_work.set(2);
+        assert_eq!(
+            worker.decide(config.view(&time, 5), &[]),
+            (Task::Sleep, "work keeps failing → sleep")
+        );
+    }
+
+    #[test]
+    fn forcing_a_worker_breaks_a_deadlock() {
+        let village = [
+            villager(Personality::Cooperative),
+            villager(Personality::Lazy),
+        ];
+        village.iter().for_each(|c| c.remember(Task::Sleep, 4));
+        assert!(Controller::stalled(&village, 0));
+        assert!(!Controller::stalled(&village, 1));
+        village[0].force(Task::Work);
+        assert!(!Controller::stalled(&village, 0));
+        assert!(village[0].forced.get());
+        assert_eq!(village[0].history.borrow().back(), Some(&Task::Work));
+    }
+
+    #[test]
+    fn greedy_characters_only_steal_inside_the_theft_window() {
+        let config = Config {
+            theft_phases: vec![Phase::Night],
+            ..Config::default()
+        };
+        let greedy = villager(Personality::Greedy);
+        for phase in GameTime::DEFAULT_ORDER {
+            let time = time_at(phase, SubPhase::Commence);
+            let task = greedy.decide(config.view(&time, 5), &[]).0;
+            assert_eq!(task == Task::Steal, phase == Phase::Night, "{:?}", phase);
+        }
+    }
+
+    #[test]
+    fn cooperative_characters_work_until_the_community_goal_is_met() {
+        let config = Config {
+            community_goal: Some(10),
+            ..Config::default()
+        };
+        let morning = time_at(Phase::Morning, SubPhase::Commence);
+        let midday = time_at(Phase::Midday, SubPhase::Commence);
+        let cooperative = villager(Personality::Cooperative);
+        assert_eq!(
+            cooperative.decide(config.view(&morning, 5), &[]).0,
+            Task::Work
+        );
+        assert_eq!(
+            cooperative.decide(config.view(&midday, 5), &[]).0,
+            Task::Work
+        );
+        assert_eq!(
+            cooperative.decide(config.view(&morning, 10), &[]).0,
+            Task::Sleep
+        );
+        assert_eq!(
+            cooperative.decide(config.view(&midday, 10), &[]).0,
+            Task::Eat
+        );
+        let greedy = villager(Personality::Greedy);
+        assert_eq!(greedy.decide(config.view(&morning, 5), &[]).0, Task::Eat);
+    }
+
+    #[test]
+    fn stock_offsets_split_borderline_decisions() {
+        let time = time_at(Phase::Morning, SubPhase::Commence);
+        let steady = villager(Personality::Greedy);
+        let eager = Character {
+            stock_offset: 1,
+            ..villager(Personality::Greedy)
+        };
+        assert_eq!(steady.decide(view(&time, 0), &[]).0, Task::Work);
+        assert_eq!(eager.decide(view(&time, 0), &[]).0, Task::Eat);
+    }
+
+    #[test]
+    fn harvest_yields_scale_with_maturity_down_to_the_floor() {
+        let config = Config {
+            maturity_exponent: 1.0,
+            min_harvest_yield: 1,
+            ..Config::default()
+        };
+        assert_eq!(config.harvest_yield(4, 0.0), 0);
+        assert_eq!(config.harvest_yield(4, 0.1), 1);
+        assert_eq!(config.harvest_yield(4, 0.5), 2);
+        assert_eq!(config.harvest_yield(4, 1.0), 4);
+    }
+
+    #[test]
+    fn unmatched_finish_rules_do_nothing() {
+        let config = Config {
+            finish_rules: vec![(Task::Work, Season::Summer, FinishEffect::Store)],
+            ..Config::default()
+        };
+        assert!(matches!(
+            config.finish_effect(Task::Work, Season::Summer),
+            FinishEffect::Store
+        ));
+        assert!(matches!(
+            config.finish_effect(Task::Work, Season::Winter),
+            FinishEffect::Nothing
+        ));
+    }
+
+    #[test]
+    fn task_icons_are_looked_up_per_task() {
+        let config = Config {
+            task_icons: [Some(String::from("res://eat.tscn")), None, None],
+            ..Config::default()
+        };
+        assert_eq!(config.task_icon(Task::Eat), Some("res://eat.tscn"));
+        assert_eq!(config.task_icon(Task::Sleep), None);
+        assert_eq!(config.task_icon(Task::Steal), None);
+    }
+
+    #[test]
+    fn success_probability_zero_yields_no_apple() {
+        let rng = Rng::seeded(3);
+        assert!((0..64).all(|_| !rng.chance(0.0)));
+        assert!((0..64).all(|_| rng.chance(1.0)));
+        let config = Config {
+            maturity_exponent: 1.0,
+            min_harvest_yield: 1,
+            ..Config::default()
+        };
+        assert_eq!(config.harvest_yield(4, 0.0), 0);
+    }
+
+    #[test]
+    fn curfew_covers_night_and_predawn_only_when_enabled() {
+        let config = Config {
+            night_curfew: true,
+            ..Config::default()
+        };
+        for phase in GameTime::DEFAULT_ORDER {
+            let night = matches!(phase, Phase::Night | Phase::Predawn);
+            assert_eq!(config.curfew(phase), night, "{:?}", phase);
+            assert!(!Config::default().curfew(phase));
+        }
+    }
+
+    #[test]
+    fn aborting_an_active_traveler_at_night_does_not_apply_its_outcome() {
+        let channel = Controller::transfer_channel(2, 0.0);
+        assert_eq!(channel.carried(), 2);
+        channel.cancel();
+        assert_eq!(channel.carried(), 0);
+        channel.fire();
+        let (outcome, rest) = channel.check(0.0);
+        assert!(outcome.is_none() && rest.is_none());
+    }
+
+    impl Branch for Vec<u32> {
+        type Apple = u32;
+
+        fn detach(&mut self) -> Option<u32> {
+            self.pop()
+        }
+
+        fn attach(&mut self, apple: u32) {
+            self.push(apple)
+        }
+    }
+
+    #[test]
+    fn picking_reduces_the_child_count_by_one() {
+        let mut tree = vec![1, 2, 3];
+        let mut picked = vec![];
+        assert_eq!(Branch::pick(&mut tree, &mut picked), Some(3));
+        assert_eq!((tree.len(), picked.len()), (2, 1));
+        Branch::pick(&mut tree, &mut picked);
+        Branch::pick(&mut tree, &mut picked);
+        assert_eq!(Branch::pick(&mut tree, &mut picked), None);
+        assert_eq!(picked.len(), 3);
+        Branch::regrow(&mut tree, &mut picked);
+        assert_eq!((tree.len(), picked.len()), (3, 0));
+    }
+
+    #[test]
+    fn settling_delivers_unfired_and_timed_channels_at_once() {
+        let cancelled = OutcomeChannel::delayed(apples(4));
+        cancelled.cancel();
+        let mux = OutcomeMux::from([
+            OutcomeChannel::delayed(apples(1)),
+            Controller::transfer_channel(-2, 5.0),
+            cancelled,
+        ]);
+        let deltas: Vec<i64> = mux
+            .settle()
+            .iter()
+            .filter_map(|outcome| match outcome {
+                Outcome::Apples { delta } => Some(*delta),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(deltas, vec![1, -2]);
+    }
+
+    #[test]
+    fn prewarming_ten_days_of_decisions_is_deterministic() {
+        let config = Config {
+            strike_threshold: Some(0.5),
+            strike_chance: 0.5,
+            ..Config::default()
+        };
+        let run = |seed| {
+            let rng = Rng::seeded(seed);
+            let village = [
+                villager(Personality::Cooperative),
+                villager(Personality::Greedy),
+                villager(Personality::Lazy),
+            ];
+            village.iter().for_each(|c| c.satisfaction.set(0.25));
+            let mut time = time_at(Phase::Predawn, SubPhase::Commence);
+            let mut stock = 3;
+            let mut tasks = vec![];
+            while time.day <= 10 {
+                for c in &village {
+                    let task = if config.strikes(c, &rng) {
+                        Task::Sleep
+                    } else {
+                        c.decide(config.view(&time, stock), &[]).0
+                    };
+                    stock += match task {
+                        Task::Work => 1,
+                        Task::Eat if stock > 0 => -1,
+                        _ => 0,
+                    };
+                    tasks.push(task);
+                }
+                time = time.next_at(SubPhase::Commence);
+            }
+            (tasks, stock)
+        };
+        assert_eq!(run(11), run(11));
+    }
+
+    #[test]
+    fn saving_mid_wait_resumes_with_the_remaining_seconds() {
+        let wait = Item::Wait {
+            seconds: 1.0,
+            sub_phases: 2,
+        };
+        let (_, rest) = wait.tick(0.25);
+        let Some(Item::Wait {
+            seconds,
+            sub_phases,
+        }) = rest
+        else {
+            panic!("the wait ended early");
+        };
+        assert_eq!((seconds, sub_phases), (0.75, 2));
+        let resumed = Item::Wait {
+            seconds,
+            sub_phases,
+        };
+        assert!(resumed.tick(1.0).1.is_none());
+    }
+
+    #[test]
+    fn restored_channels_keep_their_sequence_number() {
+        let restored = OutcomeChannel::delayed_noop().with_seq(1 << 40);
+        assert_eq!(restored.seq, 1 << 40);
+        assert!(OutcomeChannel::delayed_noop().seq > restored.seq);
+    }
+
+    #[test]
+    fn skipping_rejects_phases_outside_the_order() {
+        let time = GameTime {
+            day: 1,
+            phase: Phase::Morning,
+            sub: SubPhase::Commence,
+            order: Rc::from(&[Phase::Morning, Phase::Midday, Phase::Night][..]),
+        };
+        assert_eq!(time.parse_phase("Midday"), Ok(Phase::Midday));
+        assert!(time.parse_phase("predawn").is_err());
+        assert!(time.parse_phase("teatime").is_err());
+    }
+
+    #[test]
+    fn skipping_lands_queued_trips_and_spans_multi_sub_phase_waits() {
+        let mut queue = VecDeque::new();
+        let trips = [
+            OutcomeChannel::delayed(apples(2)),
+            Controller::transfer_channel(-1, 5.0),
+        ];
+        Item::push(&mut queue, Item::Play(OutcomeMux::from(trips)));
+        Item::push(&mut queue, Item::wait(0.5));
+        Item::push(&mut queue, Item::wait(0.5));
+        let mut settled = vec![];
+        while let Some(item) = queue.pop_front() {
+            let (outcomes, sub_phases) = item.settle();
+            let delta: i64 = outcomes
+                .iter()
+                .map(|outcome| match outcome {
+                    Outcome::Apples { delta } => *delta,
+                    _ => 0,
+                })
+                .sum();
+            settled.push((delta, sub_phases));
+        }
+        assert_eq!(settled, vec![(1, 1), (0, 2)]);
+    }
+
+    #[derive(Default)]
+    struct RecordingSink {
+        writes: Vec<(String, i64)>,
+    }
+
+    impl OutcomeSink for RecordingSink {
+        fn write(&mut self, resource: &str, amount: i64) {
+            self.writes.push((String::from(resource), amount));
+        }
+    }
+
+    impl Pile for Vec<Vector2> {
+        fn len(&self) -> usize {
+            Vec::len(self)
+        }
+
+        fn push(&mut self, offset: Vector2) {
+            Vec::push(self, offset)
+        }
+
+        fn pop(&mut self) {
+            Vec::pop(self);
+        }
+    }
+
+    fn display() -> StockDisplay<RecordingSink, Vec<Vector2>> {
+        StockDisplay::new(RecordingSink::default(), vec![])
+    }
+
+    #[test]
+    fn the_sink_records_each_stockpile_write_in_order() {
+        let config = Config::default();
+        let mut display = display();
+        for count in [3, 5, 2] {
+            display.sync(count, &config);
+        }
+        let apples = |amount| (String::from("apples"), amount);
+        assert_eq!(display.sink.writes, vec![apples(3), apples(5), apples(2)]);
+    }
+
+    #[test]
+    fn a_tick_of_apple_outcomes_writes_the_stockpile_once() {
+        let config = Config::default();
+        let mut display = display();
+        let mut stock = Stockpile::default();
+        for _ in 0..5 {
+            stock.store(1, 1);
+            display.dirty = true;
+        }
+        display.flush(stock.count(), &config);
+        display.flush(stock.count(), &config);
+        assert_eq!(display.sink.writes, vec![(String::from("apples"), 5)]);
+    }
+
+    #[test]
+    fn visible_apples_track_the_stock_up_to_the_cap() {
+        let config = Config {
+            max_visible_apples: 5,
+            ..Config::default()
+        };
+        let mut display = display();
+        for (count, visible) in [(3, 3), (8, 5), (6, 5), (2, 2), (-1, 0)] {
+            display.sync(count, &config);
+            assert_eq!(display.stack.len(), visible, "{} apples", count);
+        }
+        display.sync(4, &config);
+        let offsets: Vec<Vector2> = (0..4).map(|i| StackLayout::Grid.offset(i)).collect();
+        assert_eq!(display.stack, offsets);
+    }
+
+    #[test]
+    fn later_workers_store_less_but_never_below_the_floor() {
+        let curve = YieldCurve {
+            base: 4,
+            falloff: 1.0,
+            floor: 2,
+        };
+        assert!(curve.at(3) < curve.at(0));
+        assert_eq!((0..8).map(|worker| curve.at(worker)).min(), Some(2));
+        let flat = YieldCurve {
+            base: 1,
+            falloff: 0.0,
+            floor: 1,
+        };
+        assert!((0..8).all(|worker| flat.at(worker) == 1));
+    }
+
+    #[test]
+    fn index_assignment_ignores_node_names() {
+        let config = Config {
+            personality_by_index: true,
+            personality_weights: PersonalityWeights {
+                cooperative: 2,
+                greedy: 1,
+                lazy: 1,
+            },
+            ..Config::default()
+        };
+        let by_index: Vec<Personality> = (0..8)
+            .map(|i| config.personality_for(i, || unreachable!("names are not hashed")))
+            .collect();
+        let by_hash: Vec<Personality> = (0..8)
+            .map(|i| config.personality_weights.assign(i as u32))
+            .collect();
+        assert!(by_index == by_hash);
+        let greedy = by_index.iter().filter(|p| **p == Personality::Greedy);
+        assert_eq!(greedy.count(), 2);
+    }
+
+    #[test]
+    fn seasons_follow_five_day_bands_with_every_fourth_band_winter() {
+        let season = |day| {
+            GameTime {
+                day,
+                ..time_at(Phase::Midday, SubPhase::Commence)
+            }
+            .season()
+        };
+        for (day, expected) in [
+            (0, Season::Summer),
+            (14, Season::Summer),
+            (15, Season::Winter),
+            (19, Season::Winter),
+            (20, Season::Summer),
+            (34, Season::Summer),
+            (35, Season::Winter),
+            (40, Season::Summer),
+        ] {
+            assert_eq!(season(day), expected, "day {}", day);
+        }
+        assert_eq!(season(15).name(), "Winter");
+        assert_eq!(season(20).name(), "Summer");
+    }
+
+    #[test]
+    fn phases_and_sub_phases_map_to_their_exported_indices() {
+        let phases = [
+            (Phase::Predawn, Controller::PHASE_PREDAWN),
+            (Phase::Morning, Controller::PHASE_MORNING),
+            (Phase::Midday, Controller::PHASE_MIDDAY),
+            (Phase::Evening, Controller::PHASE_EVENING),
+            (Phase::Night, Controller::PHASE_NIGHT),
+        ];
+        for (phase, index) in phases {
+            assert_eq!(phase.index() as i64, index, "{:?}", phase);
+        }
+        let subs = [
+            (SubPhase::Commence, Controller::SUB_COMMENCE),
+            (SubPhase::Progress, Controller::SUB_PROGRESS),
+            (SubPhase::Complete, Controller::SUB_COMPLETE),
+            (SubPhase::Tempo, Controller::SUB_TEMPO),
+        ];
+        for (sub, index) in subs {
+            assert_eq!(sub.index() as i64, index, "{:?}", sub);
+        }
+    }
+
+    #[test]
+    fn finish_rules_parse_task_season_and_effect() {
+        assert!(matches!(
+            parse_finish_rule("work:summer:store"),
+            Some((Task::Work, Season::Summer, FinishEffect::Store))
+        ));
+        assert!(matches!(
+            parse_finish_rule(" Sleep : Winter : Rest "),
+            Some((Task::Sleep, Season::Winter, FinishEffect::Rest))
+        ));
+        assert!(parse_finish_rule("work:summer").is_none());
+        assert!(parse_finish_rule("work:summer:store:twice").is_none());
+        assert!(parse_finish_rule("work:autumn:store").is_none());
     }
 }